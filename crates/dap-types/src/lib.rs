@@ -437,6 +437,24 @@ pub struct DisconnectArguments {
     pub terminateDebuggee: Option<bool>,
 }
 
+/// Restarts a debug session. Clients should only call this request if the capability ‘supportsRestartRequest’ is true.
+pub enum Restart {}
+
+impl Request for Restart {
+    type Params = Option<RestartArguments>;
+    type Result = ();
+    const COMMAND: &'static str = "restart";
+}
+
+/// Arguments for ‘restart’ request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RestartArguments {
+    /**
+     * The latest version of the 'launch' or 'attach' configuration.
+     */
+    pub arguments: Option<Value>,
+}
+
 /// The client of the debug protocol must send this request at the end of the sequence of configuration requests (which was started by the ‘initialized’ event).
 pub enum ConfigurationDone {}
 
@@ -727,6 +745,65 @@ pub struct GotoTargetsResponse {
     pub targets: Vec<GotoTarget>,
 }
 
+/// Retrieves the set of all sources currently loaded by the debugged process.
+pub enum LoadedSources {}
+
+impl Request for LoadedSources {
+    type Params = Option<LoadedSourcesArguments>;
+    type Result = LoadedSourcesResponse;
+    const COMMAND: &'static str = "loadedSources";
+}
+
+/// Arguments for ‘loadedSources’ request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LoadedSourcesArguments {}
+
+/// Response to ‘loadedSources’ request.
+#[derive(Serialize, Debug)]
+pub struct LoadedSourcesResponse {
+    /**
+     * Set of loaded sources.
+     */
+    pub sources: Vec<Source>,
+}
+
+/// Modules can be retrieved from the debug adapter with this request which can either return all modules or a range of modules to support paging.
+pub enum Modules {}
+
+impl Request for Modules {
+    type Params = ModulesArguments;
+    type Result = ModulesResponse;
+    const COMMAND: &'static str = "modules";
+}
+
+/// Arguments for ‘modules’ request.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModulesArguments {
+    /**
+     * The index of the first module to return; if omitted modules start at 0.
+     */
+    pub startModule: Option<i64>,
+
+    /**
+     * The number of modules to return. If moduleCount is not specified or 0, all modules are returned.
+     */
+    pub moduleCount: Option<i64>,
+}
+
+/// Response to ‘modules’ request.
+#[derive(Serialize, Debug)]
+pub struct ModulesResponse {
+    /**
+     * All modules or range of modules.
+     */
+    pub modules: Vec<Module>,
+
+    /**
+     * The total number of modules available.
+     */
+    pub totalModules: Option<i64>,
+}
+
 /// The request starts the debuggee to run again for one step.
 ///
 /// The debug adapter first sends the response and then a ‘stopped’ event (with reason ‘step’) after the step has completed.
@@ -859,6 +936,11 @@ pub struct SetExceptionBreakpointsArguments {
      */
     pub filters: Vec<String>,
 
+    /**
+     * Set of exception filters and their options. The set of all possible exception filters is defined by the 'exceptionBreakpointFilters' capability. This attribute is only honored by a debug adapter if the capability 'supportsExceptionFilterOptions' is true. The 'filter' and 'filterOptions' sets are additive.
+     */
+    pub filterOptions: Option<Vec<ExceptionFilterOptions>>,
+
     /**
      * Configuration options for selected exceptions.
      */
@@ -1010,6 +1092,35 @@ pub struct StepInArguments {
     pub targetId: Option<i64>,
 }
 
+/// This request retrieves the possible stepIn targets for the specified stack frame.
+///
+/// These targets can be used in the ‘stepIn’ request.
+pub enum StepInTargets {}
+
+impl Request for StepInTargets {
+    type Params = StepInTargetsArguments;
+    type Result = StepInTargetsResponse;
+    const COMMAND: &'static str = "stepInTargets";
+}
+
+/// Arguments for ‘stepInTargets’ request.
+#[derive(Deserialize, Debug)]
+pub struct StepInTargetsArguments {
+    /**
+     * The stack frame for which to retrieve the possible stepIn targets.
+     */
+    pub frameId: i64,
+}
+
+/// Response to ‘stepInTargets’ request.
+#[derive(Serialize, Debug)]
+pub struct StepInTargetsResponse {
+    /**
+     * The possible stepIn targets of the specified source location.
+     */
+    pub targets: Vec<StepInTarget>,
+}
+
 /// The request starts the debuggee to run again for one step.
 pub enum StepOut {}
 
@@ -1242,6 +1353,11 @@ pub struct Capabilities {
      */
     pub supportsExceptionOptions: Option<bool>,
 
+    /**
+     * The debug adapter supports 'filterOptions' as an argument on the 'setExceptionBreakpoints' request.
+     */
+    pub supportsExceptionFilterOptions: Option<bool>,
+
     /**
      * The debug adapter supports a 'format' attribute on the stackTraceRequest, variablesRequest, and evaluateRequest.
      */
@@ -1382,7 +1498,7 @@ pub enum ExceptionBreakMode {
 }
 
 /// An ExceptionBreakpointsFilter is shown in the UI as an option for configuring how exceptions are dealt with.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ExceptionBreakpointsFilter {
     /**
      * The internal ID of the filter. This value is passed to the setExceptionBreakpoints request.
@@ -1398,6 +1514,30 @@ pub struct ExceptionBreakpointsFilter {
      * Initial value of the filter. If not specified a value 'false' is assumed.
      */
     pub default: Option<bool>,
+
+    /**
+     * Controls whether a condition can be specified for this filter option. If false or missing, a condition can not be set.
+     */
+    pub supportsCondition: Option<bool>,
+
+    /**
+     * An optional help text providing information about the condition. This string is shown as the placeholder text for a text box and must be translated.
+     */
+    pub conditionDescription: Option<String>,
+}
+
+/// An ExceptionFilterOptions is used to specify an exception filter together with a condition for the setExceptionBreakpoints request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExceptionFilterOptions {
+    /**
+     * ID of an exception filter returned by the 'exceptionBreakpointFilters' capability.
+     */
+    pub filterId: String,
+
+    /**
+     * An optional expression for conditional exceptions. The exception will break into the debugger if the result of the condition is true.
+     */
+    pub condition: Option<String>,
 }
 
 /// Detailed information about an exception that has occurred.
@@ -1484,7 +1624,7 @@ pub struct FunctionBreakpoint {
 }
 
 /// A GotoTarget describes a code location that can be used as a target in the ‘goto’ request.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct GotoTarget {
     /**
      * Unique identifier for a goto target. This is used in the goto request.
@@ -1565,6 +1705,63 @@ pub struct Message {
     pub url_label: Option<String>,
 }
 
+/// A Module object represents a row in the modules view.
+///
+/// Two attributes are mandatory: an id identifies a module in the modules view and is used in a ModuleEvent for identifying a module for adding, updating or deleting.
+/// The name is used to minimally render the module in the UI.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Module {
+    /**
+     * Unique identifier for the module.
+     */
+    pub id: i64,
+
+    /**
+     * A name of the module.
+     */
+    pub name: String,
+
+    /**
+     * Logical full path to the module. The exact definition is implementation defined, but usually this would be a full path to the on-disk file for the module.
+     */
+    pub path: Option<String>,
+
+    /**
+     * True if the module is optimized.
+     */
+    pub isOptimized: Option<bool>,
+
+    /**
+     * True if the module is considered 'user code' by a debugger that supports 'Just My Code'.
+     */
+    pub isUserCode: Option<bool>,
+
+    /**
+     * Version of Module.
+     */
+    pub version: Option<String>,
+
+    /**
+     * User understandable description of if symbols were found for the module (ex: 'Symbols Loaded', 'Symbols not found', etc.
+     */
+    pub symbolStatus: Option<String>,
+
+    /**
+     * Logical full path to the symbol file. The exact definition is implementation defined.
+     */
+    pub symbolFilePath: Option<String>,
+
+    /**
+     * Module created or modified.
+     */
+    pub dateTimeStamp: Option<String>,
+
+    /**
+     * Address range covered by this module.
+     */
+    pub addressRange: Option<String>,
+}
+
 /// A Scope is a named container for variables. Optionally a scope can map to a source or a range within a source.
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Scope {
@@ -1822,6 +2019,40 @@ pub struct StackFrameFormat {
     pub includeAll: Option<bool>,
 }
 
+/// A StepInTarget can be used in the ‘stepIn’ request and determines into which single target the stepIn request should step.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StepInTarget {
+    /**
+     * Unique identifier for a stepIn target.
+     */
+    pub id: i64,
+
+    /**
+     * The name of the stepIn target (shown in the UI).
+     */
+    pub label: String,
+
+    /**
+     * The line of the stepIn target.
+     */
+    pub line: Option<i64>,
+
+    /**
+     * Start position of the range covered by the stepIn target.
+     */
+    pub column: Option<i64>,
+
+    /**
+     * The end line of the range covered by the stepIn target.
+     */
+    pub endLine: Option<i64>,
+
+    /**
+     * End position of the range covered by the stepIn target.
+     */
+    pub endColumn: Option<i64>,
+}
+
 /// A Thread
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Thread {
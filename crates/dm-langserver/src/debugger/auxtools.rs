@@ -5,12 +5,13 @@ use std::{
     net::Ipv4Addr,
     net::SocketAddr,
     net::TcpStream,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
     thread::JoinHandle,
 };
 use std::{net::TcpListener, sync::mpsc};
 
 use super::SequenceNumber;
+use regex::Regex;
 
 enum StreamState {
     // The client is waiting for a Stream to be sent from the thread
@@ -28,12 +29,20 @@ pub struct Auxtools {
     _thread: JoinHandle<()>,
     stream: StreamState,
     last_error: Arc<RwLock<String>>,
+    runtime_filter: Arc<RwLock<Option<Regex>>>,
+    write_lock: Arc<Mutex<()>>,
 }
 
 pub struct AuxtoolsThread {
     seq: Arc<SequenceNumber>,
     responses: mpsc::Sender<Response>,
     last_error: Arc<RwLock<String>>,
+    runtime_filter: Arc<RwLock<Option<Regex>>>,
+    write_lock: Arc<Mutex<()>>,
+    // Acks for requests the thread sent itself, which the client isn't waiting on.
+    unclaimed_acks: usize,
+    // Likewise for removing one-shot breakpoints when the debuggee stops.
+    unclaimed_unsets: usize,
 }
 
 pub struct AuxtoolsScopes {
@@ -47,17 +56,25 @@ impl Auxtools {
         let addr: SocketAddr = (Ipv4Addr::LOCALHOST, port.unwrap_or(DEFAULT_PORT)).into();
         let (responses_sender, responses_receiver) = mpsc::channel();
         let last_error = Arc::new(RwLock::new("".to_owned()));
-        let stream = TcpStream::connect_timeout(&addr, std::time::Duration::from_secs(5))?;
+        let runtime_filter = Arc::new(RwLock::new(None));
+        let write_lock = Arc::new(Mutex::new(()));
+        let stream = Self::connect_with_retry(&seq, &addr)?;
 
         let thread = {
             let seq = seq.clone();
             let last_error = last_error.clone();
+            let runtime_filter = runtime_filter.clone();
+            let write_lock = write_lock.clone();
             let stream = stream.try_clone().unwrap();
             thread::spawn(move || {
                 AuxtoolsThread {
                     seq,
                     responses: responses_sender,
                     last_error,
+                    runtime_filter,
+                    write_lock,
+                    unclaimed_acks: 0,
+                    unclaimed_unsets: 0,
                 }
                 .run(stream);
             })
@@ -69,9 +86,30 @@ impl Auxtools {
             _thread: thread,
             stream: StreamState::Connected(stream),
             last_error,
+            runtime_filter,
+            write_lock,
         })
     }
 
+    // If a previous adapter crashed, the server may still be cleaning up its
+    // old connection and refuse new ones for a little while.
+    fn connect_with_retry(seq: &SequenceNumber, addr: &SocketAddr) -> std::io::Result<TcpStream> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            match TcpStream::connect_timeout(addr, std::time::Duration::from_secs(5)) {
+                Ok(stream) => return Ok(stream),
+                Err(e)
+                    if e.kind() == std::io::ErrorKind::ConnectionRefused
+                        && std::time::Instant::now() < deadline =>
+                {
+                    output!(in seq, "[auxtools] Connection refused, retrying...");
+                    thread::sleep(std::time::Duration::from_millis(250));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     pub fn listen(seq: Arc<SequenceNumber>) -> std::io::Result<(u16, Self)> {
         let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
         let port = listener.local_addr()?.port();
@@ -79,14 +117,22 @@ impl Auxtools {
         let (connection_sender, connection_receiver) = mpsc::channel();
         let (responses_sender, responses_receiver) = mpsc::channel();
         let last_error = Arc::new(RwLock::new("".to_owned()));
+        let runtime_filter = Arc::new(RwLock::new(None));
+        let write_lock = Arc::new(Mutex::new(()));
 
         let thread = {
             let seq = seq.clone();
             let last_error = last_error.clone();
+            let runtime_filter = runtime_filter.clone();
+            let write_lock = write_lock.clone();
             AuxtoolsThread {
                 seq,
                 responses: responses_sender,
                 last_error,
+                runtime_filter,
+                write_lock,
+                unclaimed_acks: 0,
+                unclaimed_unsets: 0,
             }
             .spawn_listener(listener, connection_sender)
         };
@@ -99,6 +145,8 @@ impl Auxtools {
                 _thread: thread,
                 stream: StreamState::Waiting(connection_receiver),
                 last_error,
+                runtime_filter,
+                write_lock,
             },
         ))
     }
@@ -127,11 +175,8 @@ impl Auxtools {
 
         match &mut self.stream {
             StreamState::Connected(stream) => {
-                let data = bincode::serde::encode_to_vec(&request, bincode::config::legacy())?;
-                stream.write_all(&(data.len() as u32).to_le_bytes())?;
-                stream.write_all(&data[..])?;
-                stream.flush()?;
-                Ok(())
+                let _guard = self.write_lock.lock().unwrap();
+                write_request(stream, &request)
             }
 
             _ => {
@@ -150,6 +195,15 @@ impl Auxtools {
         Ok(())
     }
 
+    pub fn is_connected(&mut self) -> bool {
+        if let StreamState::Waiting(recv) = &self.stream
+            && let Ok(stream) = recv.try_recv()
+        {
+            self.stream = StreamState::Connected(stream);
+        }
+        matches!(self.stream, StreamState::Connected(_))
+    }
+
     pub fn disconnect(&mut self) {
         debug_output!(in self.seq, "[auxtools] disconnecting");
         let _ = self.send(Request::Disconnect);
@@ -202,10 +256,19 @@ impl Auxtools {
         &mut self,
         frame_id: u32,
     ) -> Result<Option<(String, u32)>, Box<dyn std::error::Error>> {
+        Ok(self
+            .get_current_instruction(frame_id)?
+            .map(|x| (x.proc.path, x.proc.override_id)))
+    }
+
+    pub fn get_current_instruction(
+        &mut self,
+        frame_id: u32,
+    ) -> Result<Option<InstructionRef>, Box<dyn std::error::Error>> {
         self.send_or_disconnect(Request::CurrentInstruction { frame_id })?;
 
         match self.read_response_or_disconnect()? {
-            Response::CurrentInstruction(ins) => Ok(ins.map(|x| (x.proc.path, x.proc.override_id))),
+            Response::CurrentInstruction(ins) => Ok(ins),
             response => Err(Box::new(UnexpectedResponse::new(
                 "CurrentInstruction",
                 response,
@@ -406,6 +469,11 @@ impl Auxtools {
         self.last_error.read().unwrap().clone()
     }
 
+    /// Only break on runtimes whose message matches `filter`.
+    pub fn set_runtime_filter(&self, filter: Option<Regex>) {
+        *self.runtime_filter.write().unwrap() = filter;
+    }
+
     pub fn set_catch_runtimes(
         &mut self,
         should_catch: bool,
@@ -414,6 +482,17 @@ impl Auxtools {
     }
 }
 
+fn write_request(
+    stream: &mut TcpStream,
+    request: &Request,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = bincode::serde::encode_to_vec(request, bincode::config::legacy())?;
+    stream.write_all(&(data.len() as u32).to_le_bytes())?;
+    stream.write_all(&data[..])?;
+    stream.flush()?;
+    Ok(())
+}
+
 impl AuxtoolsThread {
     fn spawn_listener(
         self,
@@ -440,7 +519,11 @@ impl AuxtoolsThread {
     }
 
     // returns true if we should disconnect
-    fn handle_response(&mut self, data: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+    fn handle_response(
+        &mut self,
+        stream: &mut TcpStream,
+        data: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
         type BincodeLegacy = bincode::config::Configuration<
             bincode::config::LittleEndian,
             bincode::config::Fixint,
@@ -467,12 +550,46 @@ impl AuxtoolsThread {
                     BreakpointReason::Pause => dap_types::StoppedEvent::REASON_PAUSE,
                     BreakpointReason::Breakpoint => dap_types::StoppedEvent::REASON_BREAKPOINT,
                     BreakpointReason::Runtime(error) => {
+                        if let Some(filter) = self.runtime_filter.read().unwrap().as_ref()
+                            && !filter.is_match(&error)
+                        {
+                            output!(in self.seq, "[auxtools] Runtime (not breaking): {}", error);
+                            let _guard = self.write_lock.lock().unwrap();
+                            write_request(
+                                stream,
+                                &Request::Continue {
+                                    kind: ContinueKind::Continue,
+                                },
+                            )?;
+                            self.unclaimed_acks += 1;
+                            return Ok(false);
+                        }
                         *(self.last_error.write().unwrap()) = error.clone();
                         description = Some(error);
                         dap_types::StoppedEvent::REASON_EXCEPTION
                     }
                 };
 
+                let one_shot = self.seq.take_one_shot_breakpoints();
+                if !one_shot.is_empty() {
+                    let _guard = self.write_lock.lock().unwrap();
+                    for (path, override_id, offset) in one_shot {
+                        write_request(
+                            stream,
+                            &Request::BreakpointUnset {
+                                instruction: InstructionRef {
+                                    proc: ProcRef {
+                                        path,
+                                        override_id: override_id as u32,
+                                    },
+                                    offset: offset as u32,
+                                },
+                            },
+                        )?;
+                        self.unclaimed_unsets += 1;
+                    }
+                }
+
                 self.seq.issue_event(dap_types::StoppedEvent {
                     threadId: Some(0),
                     reason: reason.to_owned(),
@@ -482,6 +599,14 @@ impl AuxtoolsThread {
                 });
             }
 
+            Response::Ack if self.unclaimed_acks > 0 => {
+                self.unclaimed_acks -= 1;
+            }
+
+            Response::BreakpointUnset { .. } if self.unclaimed_unsets > 0 => {
+                self.unclaimed_unsets -= 1;
+            }
+
             x => {
                 self.responses.send(x)?;
             }
@@ -518,7 +643,7 @@ impl AuxtoolsThread {
                 }
             };
 
-            match self.handle_response(&buf[..]) {
+            match self.handle_response(&mut stream, &buf[..]) {
                 Ok(requested_disconnect) => {
                     if requested_disconnect {
                         eprintln!("Debug server disconnected");
@@ -0,0 +1,157 @@
+//! Runtime error filtering and details.
+
+use super::*;
+use regex::Regex;
+
+/// Maximum number of vars of `src` and `usr` to include in exception details.
+const SNAPSHOT_VAR_LIMIT: usize = 50;
+
+impl Debugger {
+    pub fn set_exception_breakpoints(
+        &mut self,
+        params: SetExceptionBreakpointsArguments,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut break_on_runtime = params
+            .filters
+            .iter()
+            .any(|x| x == EXCEPTION_FILTER_RUNTIMES);
+        let mut filter = None;
+        for option in params.filterOptions.unwrap_or_default() {
+            if option.filterId != EXCEPTION_FILTER_RUNTIMES {
+                continue;
+            }
+            break_on_runtime = true;
+            if let Some(condition) = option.condition.filter(|c| !c.trim().is_empty()) {
+                filter = Some(Regex::new(&condition)?);
+            }
+        }
+
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                let extools = extools.get()?;
+                extools.set_runtime_filter(filter);
+                extools.set_break_on_runtime(break_on_runtime);
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                auxtools.set_runtime_filter(filter);
+                auxtools.set_catch_runtimes(break_on_runtime)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn exception_info(&mut self) -> Result<ExceptionInfoResponse, Box<dyn Error>> {
+        let message = match &mut self.client {
+            DebugClient::Extools(extools) => extools
+                .get()?
+                .last_error_message()
+                .unwrap_or_default()
+                .to_owned(),
+            DebugClient::Auxtools(auxtools) => auxtools.get_last_error_message(),
+        };
+
+        let frames = self
+            .StackTrace(StackTraceArguments {
+                threadId: 0,
+                startFrame: None,
+                levels: None,
+                format: None,
+            })?
+            .stackFrames;
+
+        // Lay the text out the same way BYOND prints runtimes to world.log.
+        let mut text = format!("runtime error: {}\n", message);
+        if let Some(top) = frames.first() {
+            text.push_str(&format!("proc name: {}\n", top.name));
+            text.push_str(&format!("  source file: {}\n", frame_location(top)));
+            for name in ["usr", "src"] {
+                if let Some(snapshot) = self.var_snapshot(top.id, name) {
+                    text.push_str(&snapshot);
+                }
+            }
+        }
+
+        let mut call_stack = String::new();
+        for frame in frames.iter() {
+            call_stack.push_str(&format!("{} ({})\n", frame.name, frame_location(frame)));
+        }
+        text.push_str("  call stack:\n");
+        text.push_str(&call_stack);
+
+        let call_chain = frames
+            .iter()
+            .map(|frame| ExceptionDetails {
+                message: Some(format!("{} ({})", frame.name, frame_location(frame))),
+                typeName: Some(frame.name.clone()),
+                fullTypeName: frame.instructionPointerReference.clone(),
+                stackTrace: Some(frame_location(frame)),
+                ..Default::default()
+            })
+            .collect();
+
+        Ok(ExceptionInfoResponse {
+            exceptionId: "runtime error".to_owned(),
+            description: Some(message),
+            breakMode: ExceptionBreakMode::Always,
+            details: Some(ExceptionDetails {
+                message: Some(text),
+                typeName: Some("runtime error".to_owned()),
+                stackTrace: Some(call_stack),
+                innerException: Some(call_chain),
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Describe `src` or `usr` in the given frame, along with its vars.
+    fn var_snapshot(&mut self, frame_id: i64, name: &str) -> Option<String> {
+        let scopes = self
+            .Scopes(ScopesArguments { frameId: frame_id })
+            .ok()?
+            .scopes;
+        let arguments = scopes.iter().find(|scope| scope.name == "Arguments")?;
+        let variable = self
+            .variables(arguments.variablesReference)?
+            .into_iter()
+            .find(|var| var.name == name)?;
+
+        let mut snapshot = format!("  {}: {}\n", name, variable.value);
+        if variable.variablesReference > 0 {
+            let vars = self.variables(variable.variablesReference)?;
+            for var in vars.iter().take(SNAPSHOT_VAR_LIMIT) {
+                snapshot.push_str(&format!("    {} = {}\n", var.name, var.value));
+            }
+            if vars.len() > SNAPSHOT_VAR_LIMIT {
+                snapshot.push_str(&format!(
+                    "    ... and {} more\n",
+                    vars.len() - SNAPSHOT_VAR_LIMIT
+                ));
+            }
+        }
+        Some(snapshot)
+    }
+
+    fn variables(&mut self, variables_reference: i64) -> Option<Vec<Variable>> {
+        self.Variables(VariablesArguments {
+            variablesReference: variables_reference,
+            filter: None,
+            start: None,
+            count: None,
+            format: None,
+        })
+        .ok()
+        .map(|response| response.variables)
+    }
+}
+
+fn frame_location(frame: &StackFrame) -> String {
+    match frame
+        .source
+        .as_ref()
+        .and_then(|source| source.name.as_ref())
+    {
+        Some(file) => format!("{},{}", file, frame.line),
+        None => format!("line {}", frame.line),
+    }
+}
@@ -8,6 +8,7 @@ use std::sync::{Arc, Mutex, mpsc};
 use std::time::Duration;
 
 use ahash::RandomState;
+use regex::Regex;
 
 use super::SequenceNumber;
 use super::extools_types::*;
@@ -153,7 +154,9 @@ pub struct Extools {
     runtime_rx: mpsc::Receiver<Runtime>,
     get_list_contents_rx: mpsc::Receiver<ListContents>,
     get_source_rx: mpsc::Receiver<GetSource>,
+    proc_list_rx: mpsc::Receiver<ProcListResponse>,
     last_runtime: Option<Runtime>,
+    runtime_filter: Arc<Mutex<Option<Regex>>>,
 }
 
 impl Extools {
@@ -173,6 +176,7 @@ impl Extools {
         let (runtime_tx, runtime_rx) = mpsc::channel();
         let (get_list_contents_tx, get_list_contents_rx) = mpsc::channel();
         let (get_source_tx, get_source_rx) = mpsc::channel();
+        let (proc_list_tx, proc_list_rx) = mpsc::channel();
 
         let extools = Extools {
             seq,
@@ -185,11 +189,14 @@ impl Extools {
             runtime_rx,
             get_list_contents_rx,
             get_source_rx,
+            proc_list_rx,
             last_runtime: None,
+            runtime_filter: Default::default(),
         };
         let seq = extools.seq.clone();
         let threads = extools.threads.clone();
         let sender = extools.sender.clone();
+        let runtime_filter = extools.runtime_filter.clone();
         let thread = ExtoolsThread {
             seq,
             sender,
@@ -200,6 +207,8 @@ impl Extools {
             runtime_tx,
             get_list_contents_tx,
             get_source_tx,
+            proc_list_tx,
+            runtime_filter,
         };
         (extools, thread)
     }
@@ -287,6 +296,11 @@ impl Extools {
         self.sender.send(BreakOnRuntime(enable));
     }
 
+    /// Only break on runtimes whose message matches `filter`.
+    pub fn set_runtime_filter(&self, filter: Option<Regex>) {
+        *self.runtime_filter.lock().unwrap() = filter;
+    }
+
     pub fn set_breakpoint(&self, proc: &str, override_id: usize, offset: i64) {
         self.sender.send(BreakpointSet(ProcOffset {
             proc: proc.to_owned(),
@@ -351,6 +365,11 @@ impl Extools {
         Ok(self.get_source_rx.recv_timeout(RECV_TIMEOUT)?.0)
     }
 
+    pub fn proc_list(&self) -> Result<Vec<ProcId>, Box<dyn Error>> {
+        self.sender.send(ProcListRequest);
+        Ok(self.proc_list_rx.recv_timeout(RECV_TIMEOUT)?.0)
+    }
+
     pub fn last_error_message(&mut self) -> Option<&str> {
         while let Ok(runtime) = self.runtime_rx.try_recv() {
             self.last_runtime = Some(runtime);
@@ -392,6 +411,8 @@ struct ExtoolsThread {
     runtime_tx: mpsc::Sender<Runtime>,
     get_list_contents_tx: mpsc::Sender<ListContents>,
     get_source_tx: mpsc::Sender<GetSource>,
+    proc_list_tx: mpsc::Sender<ProcListResponse>,
+    runtime_filter: Arc<Mutex<Option<Regex>>>,
 }
 
 impl ExtoolsThread {
@@ -452,6 +473,13 @@ impl ExtoolsThread {
     }
 
     fn stopped(&self, base: dap_types::StoppedEvent) {
+        for (proc, override_id, offset) in self.seq.take_one_shot_breakpoints() {
+            self.sender.send(BreakpointUnset(ProcOffset {
+                proc,
+                override_id,
+                offset,
+            }));
+        }
         for &k in self.threads.lock().unwrap().keys() {
             if k != 0 {
                 self.seq.issue_event(dap_types::StoppedEvent {
@@ -509,6 +537,12 @@ handle_extools! {
 
     on Runtime(&mut self, runtime) {
         output!(in self.seq, "[extools] Runtime in {}: {}", runtime.proc, runtime.message);
+        if let Some(filter) = self.runtime_filter.lock().unwrap().as_ref()
+            && !filter.is_match(&runtime.message)
+        {
+            self.sender.send(BreakpointResume);
+            return Ok(());
+        }
         self.stopped(dap_types::StoppedEvent {
             reason: dap_types::StoppedEvent::REASON_EXCEPTION.to_owned(),
             text: Some(runtime.message.clone()),
@@ -546,6 +580,10 @@ handle_extools! {
         self.queue(&self.get_source_tx, response);
     }
 
+    on ProcListResponse(&mut self, response) {
+        self.queue(&self.proc_list_tx, response);
+    }
+
     on BreakOnRuntime(&mut self, _) {
         // Either it worked or it didn't, nothing we can do about it now.
     }
@@ -0,0 +1,312 @@
+//! Function breakpoints, including wildcard typepath patterns.
+//!
+//! A `*` which makes up a whole path element matches any number of path
+//! elements, so `/mob/living/*/proc/Life` matches `/mob/living` and all its
+//! subtypes, and `*/proc/attackby` matches every type. Elsewhere `*` matches
+//! any run of characters within one element, as in `/obj/item/proc/attack*`.
+
+use super::*;
+
+/// A parsed function breakpoint name.
+enum FunctionBreakpointSpec {
+    /// `/type/proc/name` or `/type/proc/name#override_id`.
+    Exact(String, usize),
+    /// A name containing `*`, with an optional `#override_id`.
+    Pattern(String, Option<usize>),
+}
+
+impl FunctionBreakpointSpec {
+    fn parse(name: &str) -> Result<FunctionBreakpointSpec, Box<dyn Error>> {
+        let mut proc = name;
+        let mut override_id = None;
+        if let Some(idx) = name.find('#') {
+            proc = &name[..idx];
+            override_id = Some(name[idx + 1..].parse()?);
+        }
+
+        if proc.contains('*') {
+            Ok(FunctionBreakpointSpec::Pattern(
+                proc.to_owned(),
+                override_id,
+            ))
+        } else {
+            Ok(FunctionBreakpointSpec::Exact(
+                proc.to_owned(),
+                override_id.unwrap_or(0),
+            ))
+        }
+    }
+}
+
+/// Expand a pattern into every matching `(proc, override_id)` in the tree.
+fn expand_pattern(
+    objtree: &ObjectTree,
+    pattern: &str,
+    only_override: Option<usize>,
+) -> Vec<(String, usize)> {
+    let mut type_pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let Some(proc_pattern) = type_pattern.pop() else {
+        return Vec::new();
+    };
+    if let Some(&"proc") | Some(&"verb") = type_pattern.last() {
+        type_pattern.pop();
+    }
+
+    let mut procs = Vec::new();
+    objtree.root().recurse(&mut |ty| {
+        let type_path: Vec<&str> = ty.path.split('/').filter(|s| !s.is_empty()).collect();
+        if !path_matches(&type_pattern, &type_path) {
+            return;
+        }
+        for name in ty.get().procs.keys() {
+            if glob_matches(proc_pattern, name) {
+                push_overrides(&mut procs, ty, name);
+            }
+        }
+    });
+
+    if let Some(only_override) = only_override {
+        procs.retain(|&(_, override_id)| override_id == only_override);
+    }
+    procs.sort();
+    procs
+}
+
+fn path_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"*", rest)) => (0..=path.len()).any(|i| path_matches(rest, &path[i..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((elem, path_rest)) => glob_matches(first, elem) && path_matches(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => match text.strip_prefix(prefix) {
+            Some(text) => (0..=text.len())
+                .filter(|&i| text.is_char_boundary(i))
+                .any(|i| glob_matches(rest, &text[i..])),
+            None => false,
+        },
+    }
+}
+
+impl Debugger {
+    /// Set the breakpoints described by `self.function_breakpoints`, removing
+    /// any which are no longer wanted.
+    pub fn apply_function_breakpoints(&mut self) -> Result<Vec<Breakpoint>, Box<dyn Error>> {
+        let file_id = FileId::default();
+
+        let mut specs = Vec::with_capacity(self.function_breakpoints.len());
+        for (name, condition) in self.function_breakpoints.iter() {
+            specs.push((FunctionBreakpointSpec::parse(name)?, condition.clone()));
+        }
+
+        let mut breakpoints = Vec::new();
+        let saved = self.saved_breakpoints.entry(file_id).or_default();
+        let mut keep = HashSet::new();
+
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                let Some(extools) = extools.as_ref() else {
+                    for _ in specs {
+                        breakpoints.push(Breakpoint {
+                            message: Some("Debugging hooks not available".to_owned()),
+                            verified: false,
+                            ..Default::default()
+                        });
+                    }
+                    return Ok(breakpoints);
+                };
+
+                for (spec, _) in specs {
+                    match spec {
+                        FunctionBreakpointSpec::Exact(proc, override_id) => {
+                            if let Some(proc_ref) = self.db.get_proc(&proc, override_id) {
+                                let offset = 0;
+                                let tup = (proc, override_id, offset);
+                                if saved.insert(tup.clone()) {
+                                    extools.set_breakpoint(&tup.0, tup.1, tup.2);
+                                }
+                                keep.insert(tup);
+                                breakpoints.push(Breakpoint {
+                                    line: Some(proc_ref.location.line as i64),
+                                    verified: true,
+                                    column: Some(0),
+                                    ..Default::default()
+                                });
+                            } else {
+                                breakpoints.push(Breakpoint {
+                                    message: Some(format!("Unknown proc {}#{}", proc, override_id)),
+                                    verified: false,
+                                    ..Default::default()
+                                });
+                            }
+                        }
+
+                        FunctionBreakpointSpec::Pattern(pattern, only_override) => {
+                            let procs = expand_pattern(&self.db.objtree, &pattern, only_override);
+                            for (proc, override_id) in procs.iter() {
+                                let tup = (proc.clone(), *override_id, 0);
+                                if saved.insert(tup.clone()) {
+                                    extools.set_breakpoint(&tup.0, tup.1, tup.2);
+                                }
+                                keep.insert(tup);
+                            }
+                            breakpoints.push(pattern_breakpoint(&pattern, procs.len()));
+                        }
+                    }
+                }
+
+                saved.retain(|k| {
+                    if !keep.contains(k) {
+                        extools.unset_breakpoint(&k.0, k.1, k.2);
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                for (spec, condition) in specs {
+                    match spec {
+                        FunctionBreakpointSpec::Exact(proc, override_id) => {
+                            let offset = 0;
+                            let tup = (proc, override_id, offset);
+
+                            saved.insert(tup.clone());
+                            keep.insert(tup.clone());
+
+                            let result = auxtools.set_breakpoint(
+                                auxtools_types::InstructionRef {
+                                    proc: auxtools_types::ProcRef {
+                                        path: tup.0,
+                                        override_id: override_id as u32,
+                                    },
+                                    offset: offset as u32,
+                                },
+                                condition,
+                            )?;
+
+                            breakpoints.push(match result {
+                                auxtools_types::BreakpointSetResult::Success { line } => {
+                                    Breakpoint {
+                                        verified: true,
+                                        line: line.map(|x| x as i64),
+                                        ..Default::default()
+                                    }
+                                }
+
+                                auxtools_types::BreakpointSetResult::Failed => Breakpoint {
+                                    verified: false,
+                                    ..Default::default()
+                                },
+                            });
+                        }
+
+                        FunctionBreakpointSpec::Pattern(pattern, only_override) => {
+                            let procs = expand_pattern(&self.db.objtree, &pattern, only_override);
+                            let mut set = 0;
+                            for (proc, override_id) in procs {
+                                let tup = (proc, override_id, 0);
+
+                                saved.insert(tup.clone());
+                                keep.insert(tup.clone());
+
+                                let result = auxtools.set_breakpoint(
+                                    auxtools_types::InstructionRef {
+                                        proc: auxtools_types::ProcRef {
+                                            path: tup.0,
+                                            override_id: override_id as u32,
+                                        },
+                                        offset: 0,
+                                    },
+                                    condition.clone(),
+                                )?;
+                                if let auxtools_types::BreakpointSetResult::Success { .. } = result
+                                {
+                                    set += 1;
+                                }
+                            }
+                            breakpoints.push(pattern_breakpoint(&pattern, set));
+                        }
+                    }
+                }
+
+                saved.retain(|k| {
+                    if !keep.contains(k) {
+                        let _ = auxtools.unset_breakpoint(&auxtools_types::InstructionRef {
+                            proc: auxtools_types::ProcRef {
+                                path: k.0.clone(),
+                                override_id: k.1 as u32,
+                            },
+                            offset: k.2 as u32,
+                        });
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+        }
+
+        Ok(breakpoints)
+    }
+
+    /// Pick up a reparsed environment, if the language server has sent one,
+    /// and re-expand function breakpoint patterns against it.
+    pub fn poll_database_updates(&mut self) {
+        let mut latest = None;
+        while let Ok(db) = self.db_updates.try_recv() {
+            latest = Some(db);
+        }
+        let Some(db) = latest else {
+            return;
+        };
+
+        self.db = db.build();
+        if self
+            .function_breakpoints
+            .iter()
+            .any(|(name, _)| name.contains('*'))
+        {
+            match self.apply_function_breakpoints() {
+                Ok(breakpoints) => {
+                    for ((name, _), bp) in self.function_breakpoints.iter().zip(breakpoints) {
+                        if name.contains('*') {
+                            output!(in self.seq, "[main] Function breakpoint {}: {}", name, bp.message.unwrap_or_default());
+                        }
+                    }
+                }
+                Err(e) => {
+                    output!(in self.seq, "[main] Error updating function breakpoints: {}", e);
+                }
+            }
+        }
+    }
+}
+
+fn pattern_breakpoint(pattern: &str, count: usize) -> Breakpoint {
+    if count == 0 {
+        Breakpoint {
+            message: Some(format!("No procs match {}", pattern)),
+            verified: false,
+            ..Default::default()
+        }
+    } else {
+        Breakpoint {
+            message: Some(format!(
+                "Matches {} proc{}",
+                count,
+                if count == 1 { "" } else { "s" }
+            )),
+            verified: true,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Launching, attaching, restarting, and detaching from the debuggee.
+
+use super::*;
+
+/// How the current session was started, so it can be restarted.
+pub enum Session {
+    Launched { dmb: String, debug: bool },
+    Attached { port: Option<u16> },
+}
+
+impl Debugger {
+    pub fn launch(&mut self, dmb: &str, debug: bool) -> Result<(), Box<dyn Error>> {
+        // Determine port number to pass if debugging is enabled.
+        let engine_params = if debug {
+            Some(match self.engine {
+                DebugEngine::Extools => {
+                    let (port, extools) = ExtoolsHolder::listen(self.seq.clone())?;
+                    self.client = DebugClient::Extools(extools);
+
+                    // Set EXTOOLS_DLL based on configuration or on bundle if available.
+                    #[allow(unused_mut)]
+                    let mut extools_dll = None;
+
+                    #[cfg(debug_assertions)]
+                    {
+                        if let Some(dll) = self.extools_dll.as_ref() {
+                            debug_output!(in self.seq, "[main] configured override: {:?}", dll);
+                            extools_dll = Some(dll.into());
+                        }
+                    }
+
+                    #[cfg(feature = "extools_bundle")]
+                    {
+                        if extools_dll.is_none() {
+                            extools_dll = Some(super::extools_bundle::extract()?);
+                        }
+                    }
+
+                    EngineParams::Extools {
+                        port,
+                        dll: extools_dll,
+                    }
+                }
+
+                DebugEngine::Auxtools => {
+                    let (port, auxtools) = Auxtools::listen(self.seq.clone())?;
+                    self.client = DebugClient::Auxtools(auxtools);
+
+                    #[allow(unused_mut)]
+                    let mut debug_server_dll = None;
+
+                    #[cfg(debug_assertions)]
+                    {
+                        if let Some(dll) = self.debug_server_dll.as_ref() {
+                            debug_output!(in self.seq, "[main] configured override: {:?}", dll);
+                            debug_server_dll = Some(dll.into());
+                        }
+                    }
+
+                    #[cfg(feature = "auxtools_bundle")]
+                    {
+                        if debug_server_dll.is_none() {
+                            debug_server_dll = Some(super::auxtools_bundle::extract()?);
+                        }
+                    }
+
+                    EngineParams::Auxtools {
+                        port,
+                        dll: debug_server_dll,
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        // Launch the subprocess.
+        self.launched = Some(Launched::new(
+            self.seq.clone(),
+            &self.dreamseeker_exe,
+            dmb,
+            engine_params,
+        )?);
+        self.session = Some(Session::Launched {
+            dmb: dmb.to_owned(),
+            debug,
+        });
+        Ok(())
+    }
+
+    pub fn attach(&mut self, port: Option<u16>) -> Result<(), Box<dyn Error>> {
+        self.client = match self.engine {
+            DebugEngine::Extools => DebugClient::Extools(ExtoolsHolder::attach(
+                self.seq.clone(),
+                port.unwrap_or(extools::DEFAULT_PORT),
+            )?),
+
+            DebugEngine::Auxtools => {
+                DebugClient::Auxtools(Auxtools::connect(self.seq.clone(), port)?)
+            }
+        };
+        self.session = Some(Session::Attached { port });
+        Ok(())
+    }
+
+    /// After attaching, report if a previous adapter left the server paused.
+    pub fn check_left_paused(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(Session::Attached { .. }) = self.session else {
+            return Ok(());
+        };
+        let DebugClient::Auxtools(auxtools) = &mut self.client else {
+            return Ok(());
+        };
+
+        if !auxtools.get_stacks()?.is_empty() {
+            self.seq.issue_event(StoppedEvent {
+                reason: StoppedEvent::REASON_PAUSE.to_owned(),
+                description: Some("Paused by a previous debugging session".to_owned()),
+                threadId: Some(0),
+                allThreadsStopped: Some(true),
+                ..Default::default()
+            });
+        }
+        Ok(())
+    }
+
+    /// Remove every breakpoint and exception filter from the server and let
+    /// it run, so a detached server isn't left waiting on nobody.
+    pub fn clear_breakpoints_and_resume(&mut self) -> Result<(), Box<dyn Error>> {
+        let saved = std::mem::take(&mut self.saved_breakpoints);
+        self.clear_one_shot_breakpoints();
+
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                let Some(extools) = extools.as_ref() else {
+                    return Ok(());
+                };
+                extools.set_break_on_runtime(false);
+                for (proc, override_id, offset) in saved.into_values().flatten() {
+                    extools.unset_breakpoint(&proc, override_id, offset);
+                }
+                extools.continue_execution();
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                if !auxtools.is_connected() {
+                    return Ok(());
+                }
+                auxtools.set_catch_runtimes(false)?;
+                for (proc, override_id, offset) in saved.into_values().flatten() {
+                    auxtools.unset_breakpoint(&auxtools_types::InstructionRef {
+                        proc: auxtools_types::ProcRef {
+                            path: proc,
+                            override_id: override_id as u32,
+                        },
+                        offset: offset as u32,
+                    })?;
+                }
+                auxtools.continue_execution()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn disconnect_client(&mut self) {
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                extools.disconnect();
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                auxtools.disconnect();
+            }
+        }
+    }
+
+    /// Detach from or terminate the debuggee.
+    pub fn disconnect(&mut self, terminate: bool) -> Result<(), Box<dyn Error>> {
+        if !terminate && let Err(e) = self.clear_breakpoints_and_resume() {
+            output!(in self.seq, "[main] Error clearing breakpoints: {}", e);
+        }
+
+        self.disconnect_client();
+
+        if let Some(launched) = self.launched.take() {
+            if terminate {
+                launched.kill()?;
+            } else {
+                launched.detach();
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompile and relaunch, or reattach, the current session. The client
+    /// reconfigures breakpoints when the new connection is initialized.
+    pub fn restart(&mut self, arguments: Option<serde_json::Value>) -> Result<(), Box<dyn Error>> {
+        // The session is left in place until a new one replaces it, so that
+        // after any error the current one can still be restarted.
+        let Some(session) = self.session.as_ref() else {
+            return Err(Box::new(GenericError("no session to restart")));
+        };
+
+        // Prefer the latest launch configuration, if the client sent one.
+        let arguments = arguments.unwrap_or_default();
+        match *session {
+            Session::Launched { ref dmb, debug } => {
+                let dmb = arguments["dmb"].as_str().unwrap_or(dmb).to_owned();
+
+                // Build first, so a compile error leaves the old server running.
+                self.compile(&dmb)?;
+                self.disconnect(true)?;
+                self.reset_session_state();
+                self.launch(&dmb, debug)
+            }
+
+            Session::Attached { port } => {
+                let port = match arguments.get("port") {
+                    Some(port) => serde_json::from_value(port.clone())?,
+                    None => port,
+                };
+
+                self.disconnect(false)?;
+                self.reset_session_state();
+                self.attach(port)
+            }
+        }
+    }
+
+    fn reset_session_state(&mut self) {
+        self.saved_breakpoints.clear();
+        self.seq.take_one_shot_breakpoints();
+        self.step_in_targets.clear();
+        self.goto_targets.clear();
+        self.disassembly_sources.clear();
+        self.stddef_dm_info = None;
+    }
+
+    /// Run DreamMaker on the `.dme` next to `dmb`.
+    fn compile(&self, dmb: &str) -> Result<(), Box<dyn Error>> {
+        let dme = std::path::Path::new(dmb).with_extension("dme");
+        let dreammaker_exe = match self.dreammaker_exe.as_ref() {
+            Some(exe) => std::path::PathBuf::from(exe),
+            // DreamMaker is installed alongside DreamSeeker.
+            None => std::path::Path::new(&self.dreamseeker_exe).with_file_name(if cfg!(windows) {
+                "dm.exe"
+            } else {
+                "DreamMaker"
+            }),
+        };
+
+        output!(in self.seq, "[main] Compiling {}...", dme.display());
+        let output = std::process::Command::new(&dreammaker_exe)
+            .arg(&dme)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .map_err(|e| format!("failed to run {}: {}", dreammaker_exe.display(), e))?;
+
+        for stream in [&output.stdout, &output.stderr] {
+            let text = String::from_utf8_lossy(stream);
+            if !text.trim().is_empty() {
+                output!(in self.seq, "{}", text.trim_end());
+            }
+        }
+
+        if !output.status.success() {
+            return Err(format!("compilation failed: {}", output.status).into());
+        }
+        Ok(())
+    }
+}
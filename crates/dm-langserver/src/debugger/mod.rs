@@ -35,17 +35,22 @@ mod auxtools;
 mod auxtools_bundle;
 mod auxtools_types;
 mod evaluate;
+mod exceptions;
 mod extools;
 mod extools_bundle;
 mod extools_types;
+mod function_breakpoints;
 mod launched;
+mod lifecycle;
+mod sources;
+mod targets;
 
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::sync::{Arc, Mutex, atomic};
+use std::sync::{Arc, Mutex, atomic, mpsc};
 
 use dm::FileId;
-use dm::objtree::ObjectTree;
+use dm::objtree::{ObjectTree, TypeRef};
 use dreammaker::config::DebugEngine;
 
 use auxtools::Auxtools;
@@ -55,6 +60,7 @@ use ahash::RandomState;
 use self::auxtools::AuxtoolsScopes;
 use self::extools::ExtoolsHolder;
 use self::launched::{EngineParams, Launched};
+use self::lifecycle::Session;
 use crate::jrpc_io;
 use dap_types::*;
 
@@ -62,9 +68,10 @@ use dap_types::*;
 pub type LineNumber = (i64, String, String, usize);
 
 pub fn start_server(
-    engine: DebugEngine,
+    config: dm::config::Debugger,
     dreamseeker_exe: String,
     db: DebugDatabaseBuilder,
+    db_updates: mpsc::Receiver<DebugDatabaseBuilder>,
 ) -> std::io::Result<(u16, std::thread::JoinHandle<()>)> {
     use std::net::*;
 
@@ -77,7 +84,8 @@ pub fn start_server(
             let (stream, _) = listener.accept().unwrap();
             drop(listener);
             let mut input = std::io::BufReader::new(stream.try_clone().unwrap());
-            let mut debugger = Debugger::new(engine, dreamseeker_exe, db, Box::new(stream));
+            let mut debugger =
+                Debugger::new(&config, dreamseeker_exe, db, db_updates, Box::new(stream));
             jrpc_io::run_with_read(&mut input, |message| debugger.handle_input(message));
         })?;
 
@@ -126,14 +134,16 @@ pub fn debugger_main<I: Iterator<Item = String>>(mut args: I) {
         debug_server_dll: None,
     };
     let mut debugger = Debugger::new(
-        ctx.config().debugger.engine,
+        &ctx.config().debugger,
         dreamseeker_exe,
         db,
+        mpsc::channel().1,
         Box::new(std::io::stdout()),
     );
     jrpc_io::run_until_stdin_eof(|message| debugger.handle_input(message));
 }
 
+#[derive(Clone)]
 pub struct DebugDatabaseBuilder {
     pub root_dir: std::path::PathBuf,
     pub files: dm::FileList,
@@ -195,11 +205,11 @@ pub struct DebugDatabase {
     line_numbers: HashMap<dm::FileId, Vec<LineNumber>, RandomState>,
 }
 
-fn get_proc<'o>(
+/// Split a `/type/proc/name` reference into the type and the proc name.
+fn split_proc_ref<'o, 'r>(
     objtree: &'o ObjectTree,
-    proc_ref: &str,
-    override_id: usize,
-) -> Option<&'o dm::objtree::ProcValue> {
+    proc_ref: &'r str,
+) -> Option<(TypeRef<'o>, &'r str)> {
     let mut bits: Vec<&str> = proc_ref.split('/').collect();
     let procname = bits.pop().unwrap();
     match bits.last() {
@@ -209,8 +219,15 @@ fn get_proc<'o>(
         _ => {}
     }
     let typename = bits.join("/");
+    objtree.find(&typename).map(|ty| (ty, procname))
+}
 
-    if let Some(ty) = objtree.find(&typename)
+fn get_proc<'o>(
+    objtree: &'o ObjectTree,
+    proc_ref: &str,
+    override_id: usize,
+) -> Option<&'o dm::objtree::ProcValue> {
+    if let Some((ty, procname)) = split_proc_ref(objtree, proc_ref)
         && let Some(ty_proc) = ty.get().procs.get(procname)
     {
         // Don't consider (most) builtins against the override_id count.
@@ -223,6 +240,28 @@ fn get_proc<'o>(
     None
 }
 
+/// Number of leading builtin `ProcValue`s which do not take an `override_id`.
+fn builtin_prefix(proc_ref: &str, values: &[dm::objtree::ProcValue]) -> usize {
+    if STDDEF_PROCS.contains(&proc_ref) {
+        return 0;
+    }
+    values
+        .iter()
+        .take_while(|pv| pv.location.is_builtins())
+        .count()
+}
+
+/// Push every debuggable override of `name` defined directly on `ty`.
+fn push_overrides(out: &mut Vec<(String, usize)>, ty: TypeRef, name: &str) {
+    if let Some(type_proc) = ty.get().procs.get(name) {
+        let proc_ref = format!("{}/{}", ty.path, name);
+        let skip = builtin_prefix(&proc_ref, &type_proc.value);
+        for override_id in 0..type_proc.value.len() - skip {
+            out.push((proc_ref.clone(), override_id));
+        }
+    }
+}
+
 impl DebugDatabase {
     fn get_proc(&self, proc_ref: &str, override_id: usize) -> Option<&dm::objtree::ProcValue> {
         get_proc(&self.objtree, proc_ref, override_id)
@@ -254,10 +293,12 @@ enum DebugClient {
 struct Debugger {
     engine: DebugEngine,
     dreamseeker_exe: String,
+    dreammaker_exe: Option<String>,
     extools_dll: Option<String>,
     debug_server_dll: Option<String>,
     db: DebugDatabase,
     launched: Option<Launched>,
+    session: Option<Session>,
     client: DebugClient,
 
     seq: Arc<SequenceNumber>,
@@ -265,22 +306,33 @@ struct Debugger {
 
     saved_breakpoints: HashMap<FileId, HashSet<(String, usize, i64)>>,
     stddef_dm_info: Option<StddefDmInfo>,
+
+    step_in_targets: Vec<Vec<(String, usize)>>,
+    goto_targets: Vec<(String, usize, i64)>,
+
+    disassembly_sources: Vec<(String, usize)>,
+
+    function_breakpoints: Vec<(String, Option<String>)>,
+    db_updates: mpsc::Receiver<DebugDatabaseBuilder>,
 }
 
 impl Debugger {
     fn new(
-        engine: DebugEngine,
+        config: &dm::config::Debugger,
         dreamseeker_exe: String,
         mut db: DebugDatabaseBuilder,
+        db_updates: mpsc::Receiver<DebugDatabaseBuilder>,
         stream: OutStream,
     ) -> Self {
         Debugger {
-            engine,
+            engine: config.engine,
             dreamseeker_exe,
+            dreammaker_exe: config.dreammaker_exe.clone(),
             extools_dll: db.extools_dll.take(),
             debug_server_dll: db.debug_server_dll.take(),
             db: db.build(),
             launched: None,
+            session: None,
             client: DebugClient::Extools(ExtoolsHolder::default()),
 
             seq: Arc::new(SequenceNumber::new(stream)),
@@ -288,6 +340,14 @@ impl Debugger {
 
             saved_breakpoints: Default::default(),
             stddef_dm_info: None,
+
+            step_in_targets: Default::default(),
+            goto_targets: Default::default(),
+
+            disassembly_sources: Default::default(),
+
+            function_breakpoints: Default::default(),
+            db_updates,
         }
     }
 
@@ -299,6 +359,7 @@ impl Debugger {
 
     fn handle_input_inner(&mut self, message: &str) -> Result<(), Box<dyn Error>> {
         let protocol_message = serde_json::from_str::<ProtocolMessage>(message)?;
+        self.poll_database_updates();
         match protocol_message.type_.as_str() {
             RequestMessage::TYPE => {
                 let request = serde_json::from_str::<RequestMessage>(message)?;
@@ -311,6 +372,7 @@ impl Debugger {
                     }
                     None => Err(format!("Request NYI: {}", request.command).into()),
                 };
+                self.release_user_breakpoints();
 
                 let response = ResponseMessage {
                     protocol_message: ProtocolMessage {
@@ -423,11 +485,19 @@ handle_request! {
             supportsFunctionBreakpoints: Some(true),
             supportsConditionalBreakpoints: Some(true),
             supportsDisassembleRequest: Some(true),
+            supportsStepInTargetsRequest: Some(true),
+            supportsGotoTargetsRequest: Some(true),
+            supportsLoadedSourcesRequest: Some(true),
+            supportsModulesRequest: Some(true),
+            supportsRestartRequest: Some(true),
+            supportsExceptionFilterOptions: Some(true),
             exceptionBreakpointFilters: Some(vec![
                 ExceptionBreakpointsFilter {
                     filter: EXCEPTION_FILTER_RUNTIMES.to_owned(),
                     label: "Runtime errors".to_owned(),
                     default: Some(true),
+                    supportsCondition: Some(true),
+                    conditionDescription: Some("Only break on runtimes matching this regex".to_owned()),
                 }
             ]),
             .. Default::default()
@@ -435,105 +505,21 @@ handle_request! {
     }
 
     on LaunchVsc(&mut self, params) {
-        // Determine port number to pass if debugging is enabled.
-        let debug = !params.base.noDebug.unwrap_or(false);
-
-        let engine_params = if debug {
-            Some(match self.engine {
-                DebugEngine::Extools => {
-                    let (port, extools) = ExtoolsHolder::listen(self.seq.clone())?;
-                    self.client = DebugClient::Extools(extools);
-
-                    // Set EXTOOLS_DLL based on configuration or on bundle if available.
-                    #[allow(unused_mut)]
-                    let mut extools_dll = None;
-
-                    #[cfg(debug_assertions)] {
-                        if let Some(dll) = self.extools_dll.as_ref() {
-                            debug_output!(in self.seq, "[main] configured override: {:?}", dll);
-                            extools_dll = Some(dll.into());
-                        }
-                    }
-
-                    #[cfg(feature = "extools_bundle")] {
-                        if extools_dll.is_none() {
-                            extools_dll = Some(self::extools_bundle::extract()?);
-                        }
-                    }
-
-                    EngineParams::Extools {
-                        port,
-                        dll: extools_dll,
-                    }
-                }
-
-                DebugEngine::Auxtools => {
-                    let (port, auxtools) = Auxtools::listen(self.seq.clone())?;
-                    self.client = DebugClient::Auxtools(auxtools);
-
-                    #[allow(unused_mut)]
-                    let mut debug_server_dll = None;
-
-                    #[cfg(debug_assertions)] {
-                        if let Some(dll) = self.debug_server_dll.as_ref() {
-                            debug_output!(in self.seq, "[main] configured override: {:?}", dll);
-                            debug_server_dll = Some(dll.into());
-                        }
-                    }
-
-                    #[cfg(feature = "auxtools_bundle")] {
-                        if debug_server_dll.is_none() {
-                            debug_server_dll = Some(self::auxtools_bundle::extract()?);
-                        }
-                    }
-
-                    EngineParams::Auxtools {
-                        port,
-                        dll: debug_server_dll,
-                    }
-                }
-            })
-        } else {
-            None
-        };
-
-        // Launch the subprocess.
-        self.launched = Some(Launched::new(self.seq.clone(), &self.dreamseeker_exe, &params.dmb, engine_params)?);
+        self.launch(&params.dmb, !params.base.noDebug.unwrap_or(false))?;
     }
 
     on AttachVsc(&mut self, params) {
-        self.client = match self.engine {
-            DebugEngine::Extools => {
-                DebugClient::Extools(ExtoolsHolder::attach(self.seq.clone(), params.port.unwrap_or(extools::DEFAULT_PORT))?)
-            }
+        self.attach(params.port)?;
+    }
 
-            DebugEngine::Auxtools => {
-                DebugClient::Auxtools(Auxtools::connect(self.seq.clone(), params.port)?)
-            }
-        };
+    on Restart(&mut self, params) {
+        self.restart(params.and_then(|params| params.arguments))?;
     }
 
     on Disconnect(&mut self, params) {
         let default_terminate = self.launched.is_some();
         let terminate = params.terminateDebuggee.unwrap_or(default_terminate);
-
-        match &mut self.client {
-            DebugClient::Extools(extools) => {
-                extools.disconnect();
-            }
-
-            DebugClient::Auxtools(auxtools) => {
-                auxtools.disconnect();
-            }
-        }
-
-        if let Some(launched) = self.launched.take() {
-            if terminate {
-                launched.kill()?;
-            } else {
-                launched.detach();
-            }
-        }
+        self.disconnect(terminate)?;
     }
 
     on ConfigurationDone(&mut self, ()) {
@@ -552,6 +538,7 @@ handle_request! {
                 auxtools.configured()?;
             }
         }
+        self.check_left_paused()?;
     }
 
     on Threads(&mut self, ()) {
@@ -768,132 +755,13 @@ handle_request! {
     }
 
     on SetFunctionBreakpoints(&mut self, params) {
-        let file_id = FileId::default();
-
-        let inputs = params.breakpoints;
-        let mut breakpoints = Vec::new();
-        let saved = self.saved_breakpoints.entry(file_id).or_default();
-        let mut keep = HashSet::new();
-
-        match &mut self.client {
-            DebugClient::Extools(extools) => {
-                let Some(extools) = extools.as_ref() else {
-                    for _ in inputs {
-                        breakpoints.push(Breakpoint {
-                            message: Some("Debugging hooks not available".to_owned()),
-                            verified: false,
-                            .. Default::default()
-                        });
-                    }
-                    return Ok(SetFunctionBreakpointsResponse { breakpoints });
-                };
-
-                for sbp in inputs {
-                    // parse function reference
-                    let mut proc = &sbp.name[..];
-                    let mut override_id = 0;
-                    if let Some(idx) = sbp.name.find('#') {
-                        proc = &sbp.name[..idx];
-                        override_id = sbp.name[idx+1..].parse()?;
-                    }
-
-                    if let Some(proc_ref) = self.db.get_proc(proc, override_id) {
-                        let offset = 0;
-                        let tup = (proc.to_owned(), override_id, offset);
-                        if saved.insert(tup.clone()) {
-                            extools.set_breakpoint(&tup.0, tup.1, tup.2);
-                        }
-                        keep.insert(tup);
-                        breakpoints.push(Breakpoint {
-                            line: Some(proc_ref.location.line as i64),
-                            verified: true,
-                            column: Some(0),
-                            .. Default::default()
-                        });
-                    } else {
-                        breakpoints.push(Breakpoint {
-                            message: Some(format!("Unknown proc {}#{}", proc, override_id)),
-                            verified: false,
-                            .. Default::default()
-                        });
-                    }
-                }
-
-                saved.retain(|k| {
-                    if !keep.contains(k) {
-                        extools.unset_breakpoint(&k.0, k.1, k.2);
-                        false
-                    } else {
-                        true
-                    }
-                });
-
-                SetFunctionBreakpointsResponse { breakpoints }
-            }
-
-
-            DebugClient::Auxtools(auxtools) => {
-                let mut breakpoints = vec![];
-
-                for sbp in inputs {
-                    // parse function reference
-                    let mut proc = &sbp.name[..];
-                    let mut override_id = 0;
-                    if let Some(idx) = sbp.name.find('#') {
-                        proc = &sbp.name[..idx];
-                        override_id = sbp.name[idx+1..].parse()?;
-                    }
-
-                    let offset = 0;
-                    let tup = (proc.to_owned(), override_id, offset);
-
-                    saved.insert(tup.clone());
-                    keep.insert(tup.clone());
-
-                    let result = auxtools.set_breakpoint(auxtools_types::InstructionRef {
-                        proc: auxtools_types::ProcRef {
-                            path: tup.0,
-                            override_id: override_id as u32
-                        },
-                        offset: offset as u32,
-                    }, sbp.condition)?;
-
-                    breakpoints.push(match result {
-                        auxtools_types::BreakpointSetResult::Success { line } => {
-                            Breakpoint {
-                                verified: true,
-                                line: line.map(|x| x as i64),
-                                .. Default::default()
-                            }
-                        },
-
-                        auxtools_types::BreakpointSetResult::Failed => {
-                            Breakpoint {
-                                verified: false,
-                                .. Default::default()
-                            }
-                        }
-                    });
-                }
-
-                saved.retain(|k| {
-                    if !keep.contains(k) {
-                        let _ = auxtools.unset_breakpoint(&auxtools_types::InstructionRef {
-                            proc: auxtools_types::ProcRef {
-                                path: k.0.clone(),
-                                override_id: k.1 as u32
-                            },
-                            offset: k.2 as u32,
-                        });
-                        false
-                    } else {
-                        true
-                    }
-                });
-
-                SetFunctionBreakpointsResponse { breakpoints }
-            }
-        }
+        self.function_breakpoints = params
+            .breakpoints
+            .into_iter()
+            .map(|sbp| (sbp.name, sbp.condition))
+            .collect();
+        let breakpoints = self.apply_function_breakpoints()?;
+        SetFunctionBreakpointsResponse { breakpoints }
     }
 
     on StackTrace(&mut self, params) {
@@ -1286,6 +1154,10 @@ handle_request! {
     }
 
     on StepIn(&mut self, params) {
+        if let Some(target_id) = params.targetId {
+            return self.step_in_target(params.threadId, target_id);
+        }
+
         self.notify_continue();
 
         match &mut self.client {
@@ -1315,6 +1187,10 @@ handle_request! {
         }
     }
 
+    on StepInTargets(&mut self, params) {
+        self.step_in_targets(params.frameId)?
+    }
+
     on StepOut(&mut self, params) {
         self.notify_continue();
 
@@ -1343,42 +1219,20 @@ handle_request! {
         }
     }
 
-    on SetExceptionBreakpoints(&mut self, params) {
-        match &mut self.client {
-            DebugClient::Extools(extools) => {
-                let extools = extools.get()?;
-                extools.set_break_on_runtime(params.filters.iter().any(|x| x == EXCEPTION_FILTER_RUNTIMES));
-            }
+    on GotoTargets(&mut self, params) {
+        self.goto_targets(params)?
+    }
 
-            DebugClient::Auxtools(auxtools) => {
-                auxtools.set_catch_runtimes(params.filters.iter().any(|x| x == EXCEPTION_FILTER_RUNTIMES))?;
-            }
-        }
+    on Goto(&mut self, params) {
+        self.run_to_target(params.targetId)?;
     }
 
-    on ExceptionInfo(&mut self, _params) {
-        match &mut self.client {
-            DebugClient::Extools(extools) => {
-                let extools = extools.get()?;
-                // VSC shows exceptionId, description, stackTrace in that order.
-                let message = extools.last_error_message();
-                ExceptionInfoResponse {
-                    exceptionId: message.unwrap_or_default().to_owned(),
-                    description: None,
-                    breakMode: ExceptionBreakMode::Always,
-                    details: None,
-                }
-            }
+    on SetExceptionBreakpoints(&mut self, params) {
+        self.set_exception_breakpoints(params)?;
+    }
 
-            DebugClient::Auxtools(auxtools) => {
-                ExceptionInfoResponse {
-                    exceptionId: auxtools.get_last_error_message(),
-                    description: None,
-                    breakMode: ExceptionBreakMode::Always,
-                    details: None,
-                }
-            }
-        }
+    on ExceptionInfo(&mut self, _params) {
+        self.exception_info()?
     }
 
     on Evaluate(&mut self, params) {
@@ -1393,7 +1247,7 @@ handle_request! {
             }
 
         if source_reference != STDDEF_SOURCE_REFERENCE {
-            return Err(Box::new(GenericError("Unknown source reference")));
+            return Ok(SourceResponse::from(self.disassembly_text(source_reference)?));
         }
 
         if let Some(info) = self.stddef_dm_info.as_ref() {
@@ -1403,6 +1257,14 @@ handle_request! {
         }
     }
 
+    on LoadedSources(&mut self, _params) {
+        self.loaded_sources()?
+    }
+
+    on Modules(&mut self, params) {
+        self.modules(params)?
+    }
+
     on Disassemble(&mut self, params) {
         match &mut self.client {
             DebugClient::Extools(extools) => {
@@ -1469,6 +1331,8 @@ type OutStream = Box<dyn std::io::Write + Send>;
 
 pub struct SequenceNumber {
     seq: atomic::AtomicI64,
+    /// Internal breakpoints to remove the next time the debuggee stops.
+    one_shot_breakpoints: Mutex<Vec<(String, usize, i64)>>,
     stream: Mutex<OutStream>,
 }
 
@@ -1476,6 +1340,7 @@ impl SequenceNumber {
     fn new(stream: OutStream) -> SequenceNumber {
         SequenceNumber {
             seq: Default::default(),
+            one_shot_breakpoints: Default::default(),
             stream: Mutex::new(stream),
         }
     }
@@ -1484,6 +1349,17 @@ impl SequenceNumber {
         self.seq.fetch_add(1, atomic::Ordering::Relaxed)
     }
 
+    fn one_shot_breakpoints(&self) -> std::sync::MutexGuard<'_, Vec<(String, usize, i64)>> {
+        self.one_shot_breakpoints
+            .lock()
+            .expect("one-shot breakpoints poisoned")
+    }
+
+    /// Take the one-shot breakpoints, for the debuggee having stopped.
+    fn take_one_shot_breakpoints(&self) -> Vec<(String, usize, i64)> {
+        std::mem::take(&mut *self.one_shot_breakpoints())
+    }
+
     fn issue_event<E: Event>(&self, event: E) {
         let body = serde_json::to_value(event).expect("event body encode error");
         let message = EventMessage {
@@ -1649,6 +1525,8 @@ const STDDEF_PROCS: &[&str] = &[
 ];
 
 const STDDEF_SOURCE_REFERENCE: i64 = 1;
+// Source references from here on up are disassembly of individual procs.
+const DISASSEMBLY_SOURCE_REFERENCE_BASE: i64 = 2;
 
 struct StddefDmInfo {
     text: String,
@@ -0,0 +1,198 @@
+//! Loaded sources and the browser of compiled procs.
+
+use super::*;
+
+/// Where the code for a compiled proc can be found.
+enum ProcSource {
+    /// A file in the environment, with the proc's line.
+    File(String, i64),
+    /// The internal `stddef.dm`, with the proc's line.
+    Stddef(i64),
+    /// Nowhere; only its disassembly is available.
+    Disassembly,
+}
+
+impl Debugger {
+    /// List every proc the debuggee has compiled, as `(path, override_id)`.
+    fn compiled_procs(&mut self) -> Result<Vec<(String, usize)>, Box<dyn Error>> {
+        let mut procs: Vec<(String, usize)> = match &mut self.client {
+            DebugClient::Extools(extools) => extools
+                .get()?
+                .proc_list()?
+                .into_iter()
+                .map(|id| (id.proc, id.override_id))
+                .collect(),
+
+            // Auxtools can't enumerate procs, so trust the environment we parsed.
+            DebugClient::Auxtools(_) => self
+                .db
+                .line_numbers
+                .values()
+                .flatten()
+                .map(|(_, type_path, name, override_id)| {
+                    (format!("{}/{}", type_path, name), *override_id)
+                })
+                .collect(),
+        };
+        procs.sort();
+        procs.dedup();
+        Ok(procs)
+    }
+
+    fn locate_proc(&self, proc: &str, override_id: usize) -> ProcSource {
+        let Some(pv) = self.db.get_proc(proc, override_id) else {
+            return ProcSource::Disassembly;
+        };
+
+        if !pv.location.is_builtins() {
+            let path = self.db.files.get_path(pv.location.file);
+            return ProcSource::File(
+                self.db.root_dir.join(path).to_string_lossy().into_owned(),
+                i64::from(pv.location.line),
+            );
+        }
+
+        if let Some(stddef_dm_info) = self.stddef_dm_info.as_ref()
+            && let Some(pv) = get_proc(&stddef_dm_info.objtree, proc, override_id)
+        {
+            return ProcSource::Stddef(i64::from(pv.location.line));
+        }
+
+        ProcSource::Disassembly
+    }
+
+    /// Get a virtual source showing the disassembly of a proc.
+    fn disassembly_source(&mut self, proc: &str, override_id: usize) -> Source {
+        let key = (proc.to_owned(), override_id);
+        let idx = match self
+            .disassembly_sources
+            .iter()
+            .position(|each| *each == key)
+        {
+            Some(idx) => idx,
+            None => {
+                self.disassembly_sources.push(key);
+                self.disassembly_sources.len() - 1
+            }
+        };
+
+        Source {
+            name: Some(format!("{} (disassembly)", proc_label(proc, override_id))),
+            sourceReference: Some(DISASSEMBLY_SOURCE_REFERENCE_BASE + idx as i64),
+            presentationHint: Some(SourcePresentationHint::Deemphasize),
+            origin: Some("disassembly".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    /// Get the text of a source handed out by `disassembly_source`.
+    pub fn disassembly_text(&mut self, source_reference: i64) -> Result<String, Box<dyn Error>> {
+        let Some((proc, override_id)) =
+            usize::try_from(source_reference - DISASSEMBLY_SOURCE_REFERENCE_BASE)
+                .ok()
+                .and_then(|idx| self.disassembly_sources.get(idx))
+        else {
+            return Err(Box::new(GenericError("Unknown source reference")));
+        };
+
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                let bytecode = extools.get()?.bytecode(proc, *override_id);
+                Ok(Self::format_disassembly(bytecode))
+            }
+
+            DebugClient::Auxtools(_) => {
+                Err(Box::new(GenericError("auxtools can't disassemble yet")))
+            }
+        }
+    }
+
+    pub fn loaded_sources(&mut self) -> Result<LoadedSourcesResponse, Box<dyn Error>> {
+        let mut sources = Vec::new();
+        let mut seen_files = HashSet::new();
+        let mut seen_stddef = false;
+
+        for (proc, override_id) in self.compiled_procs()? {
+            match self.locate_proc(&proc, override_id) {
+                ProcSource::File(path, _) => {
+                    if seen_files.insert(path.clone()) {
+                        sources.push(Source {
+                            name: std::path::Path::new(&path)
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned()),
+                            path: Some(path),
+                            ..Default::default()
+                        });
+                    }
+                }
+                ProcSource::Stddef(_) => {
+                    if !seen_stddef {
+                        seen_stddef = true;
+                        sources.push(Source {
+                            name: Some("stddef.dm".to_owned()),
+                            sourceReference: Some(STDDEF_SOURCE_REFERENCE),
+                            ..Default::default()
+                        });
+                    }
+                }
+                ProcSource::Disassembly => {
+                    sources.push(self.disassembly_source(&proc, override_id));
+                }
+            }
+        }
+
+        Ok(LoadedSourcesResponse { sources })
+    }
+
+    pub fn modules(&mut self, params: ModulesArguments) -> Result<ModulesResponse, Box<dyn Error>> {
+        let procs = self.compiled_procs()?;
+        let total = procs.len();
+
+        let start = params.startModule.unwrap_or(0).max(0) as usize;
+        let count = match params.moduleCount {
+            Some(count) if count > 0 => count as usize,
+            _ => total,
+        };
+
+        let mut modules = Vec::new();
+        for (id, (proc, override_id)) in procs.into_iter().enumerate().skip(start).take(count) {
+            let (path, symbol_status, is_user_code) = match self.locate_proc(&proc, override_id) {
+                ProcSource::File(path, line) => {
+                    (Some(format!("{}:{}", path, line)), "Source", true)
+                }
+                ProcSource::Stddef(line) => (
+                    Some(format!("stddef.dm:{}", line)),
+                    "Source (stddef.dm)",
+                    false,
+                ),
+                ProcSource::Disassembly => {
+                    let source = self.disassembly_source(&proc, override_id);
+                    (source.name, "Disassembly only", false)
+                }
+            };
+
+            modules.push(Module {
+                id: id as i64,
+                name: proc_label(&proc, override_id),
+                path,
+                isUserCode: Some(is_user_code),
+                symbolStatus: Some(symbol_status.to_owned()),
+                ..Default::default()
+            });
+        }
+
+        Ok(ModulesResponse {
+            modules,
+            totalModules: Some(total as i64),
+        })
+    }
+}
+
+/// Format a proc the same way function breakpoints are specified.
+fn proc_label(proc: &str, override_id: usize) -> String {
+    if override_id == 0 {
+        proc.to_owned()
+    } else {
+        format!("{}#{}", proc, override_id)
+    }
+}
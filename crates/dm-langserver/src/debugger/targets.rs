@@ -0,0 +1,747 @@
+//! Step-in targets, run to cursor, and the one-shot breakpoints behind them.
+//!
+//! Neither debug engine knows how to step into a particular call or run to a
+//! particular line, so both are built out of internal breakpoints which are
+//! removed again as soon as the debuggee stops for any reason.
+
+use dm::Location;
+use dm::ast::*;
+use dm::objtree::{ProcRef, ProcValue};
+
+use super::*;
+
+/// A call which appears on the line being stepped.
+enum CallTarget<'o> {
+    /// `foo()`, on `src` or else a global proc.
+    Unscoped(&'o str),
+    /// `global.foo()` or `::foo()`.
+    Global(&'o str),
+    /// `x.foo()`, on the static type of `x` if it is known, or else on any
+    /// type at all.
+    Method(&'o str, Option<TypeRef<'o>>),
+    /// `.()`, the current proc.
+    SelfCall,
+    /// `..()`, the parent proc.
+    ParentCall,
+    /// `new /type()`.
+    New(&'o Prefab),
+}
+
+/// Collects calls made by the statements starting on a given line.
+struct CallCollector<'o> {
+    objtree: &'o ObjectTree,
+    /// The type the proc is defined on.
+    ty: TypeRef<'o>,
+    /// The declared types of the proc's parameters and local vars, or `None`
+    /// for ones which are untyped or declared more than once with different
+    /// types.
+    locals: HashMap<&'o str, Option<TypeRef<'o>>>,
+    line: u32,
+    found: Vec<(Location, CallTarget<'o>)>,
+}
+
+impl<'o> CallCollector<'o> {
+    fn new(objtree: &'o ObjectTree, ty: TypeRef<'o>, proc: &'o ProcValue, line: u32) -> Self {
+        let mut collector = CallCollector {
+            objtree,
+            ty,
+            locals: HashMap::new(),
+            line,
+            found: Vec::new(),
+        };
+        for param in proc.parameters.iter() {
+            collector.declare(&param.name, &param.var_type);
+        }
+        if let Some(code) = &proc.code {
+            collector.declare_locals(code);
+        }
+        collector
+    }
+
+    fn declare(&mut self, name: &'o str, var_type: &VarType) {
+        let ty = self.type_of(var_type);
+        self.locals
+            .entry(name)
+            .and_modify(|existing| {
+                if *existing != ty {
+                    *existing = None;
+                }
+            })
+            .or_insert(ty);
+    }
+
+    fn declare_locals(&mut self, block: &'o [Spanned<Statement>]) {
+        for stmt in block.iter() {
+            match &stmt.elem {
+                Statement::Var(var) => self.declare(&var.name, &var.var_type),
+                Statement::Vars(vars) => {
+                    for var in vars.iter() {
+                        self.declare(&var.name, &var.var_type);
+                    }
+                }
+                Statement::While { block, .. }
+                | Statement::DoWhile { block, .. }
+                | Statement::ForInfinite { block }
+                | Statement::ForLoop { block, .. }
+                | Statement::Spawn { block, .. }
+                | Statement::Label { block, .. } => self.declare_locals(block),
+                Statement::ForList(for_list) => self.declare_locals(&for_list.block),
+                Statement::ForKeyValue(for_key_value) => self.declare_locals(&for_key_value.block),
+                Statement::ForRange(for_range) => self.declare_locals(&for_range.block),
+                Statement::If { arms, else_arm } => {
+                    for (_, block) in arms.iter() {
+                        self.declare_locals(block);
+                    }
+                    if let Some(else_arm) = else_arm {
+                        self.declare_locals(else_arm);
+                    }
+                }
+                Statement::Switch { cases, default, .. } => {
+                    for (_, block) in cases.iter() {
+                        self.declare_locals(block);
+                    }
+                    if let Some(default) = default {
+                        self.declare_locals(default);
+                    }
+                }
+                Statement::TryCatch {
+                    try_block,
+                    catch_block,
+                    ..
+                } => {
+                    self.declare_locals(try_block);
+                    self.declare_locals(catch_block);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn type_of(&self, var_type: &VarType) -> Option<TypeRef<'o>> {
+        if var_type.type_path.is_empty() {
+            return None;
+        }
+        self.objtree.type_by_path(var_type.type_path.iter())
+    }
+
+    /// The static type of a term used as the receiver of a call, if known.
+    fn term_type(&self, term: &Term) -> Option<TypeRef<'o>> {
+        match term {
+            Term::Ident(name) if name == "src" => Some(self.ty).filter(|ty| !ty.is_root()),
+            Term::Ident(name) => match self.locals.get(name.as_str()) {
+                Some(ty) => *ty,
+                None => self
+                    .ty
+                    .get_var_declaration(name)
+                    .and_then(|decl| self.type_of(&decl.var_type)),
+            },
+            _ => None,
+        }
+    }
+
+    fn visit_block(&mut self, block: &'o [Spanned<Statement>]) {
+        for stmt in block.iter() {
+            self.visit_statement(stmt.location, &stmt.elem);
+        }
+    }
+
+    fn visit_statement(&mut self, location: Location, statement: &'o Statement) {
+        // Only the statement's own expressions belong to its line; nested
+        // blocks are checked against their own locations.
+        let here = location.line == self.line;
+        match statement {
+            Statement::Expr(expr)
+            | Statement::Throw(expr)
+            | Statement::Del(expr)
+            | Statement::Setting { value: expr, .. } => {
+                if here {
+                    self.visit_expression(expr);
+                }
+            }
+            Statement::Return(expr) | Statement::Crash(expr) => {
+                if here && let Some(expr) = expr {
+                    self.visit_expression(expr);
+                }
+            }
+            Statement::While { condition, block } => {
+                if here {
+                    self.visit_expression(condition);
+                }
+                self.visit_block(block);
+            }
+            Statement::DoWhile { block, condition } => {
+                self.visit_block(block);
+                if condition.location.line == self.line {
+                    self.visit_expression(&condition.elem);
+                }
+            }
+            Statement::If { arms, else_arm } => {
+                for (condition, block) in arms.iter() {
+                    if condition.location.line == self.line {
+                        self.visit_expression(&condition.elem);
+                    }
+                    self.visit_block(block);
+                }
+                if let Some(else_arm) = else_arm {
+                    self.visit_block(else_arm);
+                }
+            }
+            Statement::ForInfinite { block } => self.visit_block(block),
+            Statement::ForLoop {
+                init,
+                test,
+                inc,
+                block,
+            } => {
+                if let Some(init) = init {
+                    self.visit_statement(location, init);
+                }
+                if here && let Some(test) = test {
+                    self.visit_expression(test);
+                }
+                if let Some(inc) = inc {
+                    self.visit_statement(location, inc);
+                }
+                self.visit_block(block);
+            }
+            Statement::ForList(for_list) => {
+                if here && let Some(in_list) = &for_list.in_list {
+                    self.visit_expression(in_list);
+                }
+                self.visit_block(&for_list.block);
+            }
+            Statement::ForKeyValue(for_key_value) => {
+                if here && let Some(in_list) = &for_key_value.in_list {
+                    self.visit_expression(in_list);
+                }
+                self.visit_block(&for_key_value.block);
+            }
+            Statement::ForRange(for_range) => {
+                if here {
+                    self.visit_expression(&for_range.start);
+                    self.visit_expression(&for_range.end);
+                    if let Some(step) = &for_range.step {
+                        self.visit_expression(step);
+                    }
+                }
+                self.visit_block(&for_range.block);
+            }
+            Statement::Var(var) => {
+                if here && let Some(value) = &var.value {
+                    self.visit_expression(value);
+                }
+            }
+            Statement::Vars(vars) => {
+                if here {
+                    for value in vars.iter().filter_map(|var| var.value.as_ref()) {
+                        self.visit_expression(value);
+                    }
+                }
+            }
+            Statement::Spawn { delay, block } => {
+                if here && let Some(delay) = delay {
+                    self.visit_expression(delay);
+                }
+                self.visit_block(block);
+            }
+            Statement::Switch {
+                input,
+                cases,
+                default,
+            } => {
+                if here {
+                    self.visit_expression(input);
+                }
+                for (_, block) in cases.iter() {
+                    self.visit_block(block);
+                }
+                if let Some(default) = default {
+                    self.visit_block(default);
+                }
+            }
+            Statement::TryCatch {
+                try_block,
+                catch_block,
+                ..
+            } => {
+                self.visit_block(try_block);
+                self.visit_block(catch_block);
+            }
+            Statement::Label { block, .. } => self.visit_block(block),
+            Statement::Continue(_) | Statement::Break(_) | Statement::Goto(_) => {}
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'o Expression) {
+        match expression {
+            Expression::Base { term, follow } => {
+                self.visit_term(term.location, &term.elem);
+                let mut receiver = self.term_type(&term.elem);
+                for each in follow.iter() {
+                    self.visit_follow(each.location, &each.elem, receiver);
+                    receiver = match &each.elem {
+                        Follow::Field(_, name) => receiver
+                            .and_then(|ty| ty.get_var_declaration(name))
+                            .and_then(|decl| self.type_of(&decl.var_type)),
+                        _ => None,
+                    };
+                }
+            }
+            Expression::BinaryOp { lhs, rhs, .. } | Expression::AssignOp { lhs, rhs, .. } => {
+                self.visit_expression(lhs);
+                self.visit_expression(rhs);
+            }
+            Expression::TernaryOp { cond, if_, else_ } => {
+                self.visit_expression(cond);
+                self.visit_expression(if_);
+                self.visit_expression(else_);
+            }
+        }
+    }
+
+    fn visit_term(&mut self, location: Location, term: &'o Term) {
+        match term {
+            Term::Call(name, args) => {
+                self.found.push((location, CallTarget::Unscoped(name)));
+                self.visit_arguments(args);
+            }
+            Term::GlobalCall(name, args) => {
+                self.found.push((location, CallTarget::Global(name)));
+                self.visit_arguments(args);
+            }
+            Term::SelfCall(args) => {
+                self.found.push((location, CallTarget::SelfCall));
+                self.visit_arguments(args);
+            }
+            Term::ParentCall(args) => {
+                self.found.push((location, CallTarget::ParentCall));
+                self.visit_arguments(args);
+            }
+            Term::NewPrefab { prefab, args } => {
+                self.found.push((location, CallTarget::New(prefab)));
+                if let Some(args) = args {
+                    self.visit_arguments(args);
+                }
+            }
+            Term::NewImplicit { args } | Term::NewMiniExpr { args, .. } => {
+                if let Some(args) = args {
+                    self.visit_arguments(args);
+                }
+            }
+            Term::Expr(expr) => self.visit_expression(expr),
+            Term::InterpString(_, parts) => {
+                for expr in parts.iter().filter_map(|(expr, _)| expr.as_ref()) {
+                    self.visit_expression(expr);
+                }
+            }
+            Term::List(args) => self.visit_arguments(args),
+            Term::Locate { args, in_list } | Term::Input { args, in_list, .. } => {
+                self.visit_arguments(args);
+                if let Some(in_list) = in_list {
+                    self.visit_expression(in_list);
+                }
+            }
+            Term::Pick(args) => {
+                for (weight, value) in args.iter() {
+                    if let Some(weight) = weight {
+                        self.visit_expression(weight);
+                    }
+                    self.visit_expression(value);
+                }
+            }
+            Term::DynamicCall(args_1, args_2) => {
+                self.visit_arguments(args_1);
+                self.visit_arguments(args_2);
+            }
+            Term::ExternalCall {
+                library_name,
+                function_name,
+                args,
+            } => {
+                self.visit_expression(library_name);
+                self.visit_expression(function_name);
+                self.visit_arguments(args);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_follow(
+        &mut self,
+        location: Location,
+        follow: &'o Follow,
+        receiver: Option<TypeRef<'o>>,
+    ) {
+        match follow {
+            Follow::Call(_, name, args) => {
+                self.found
+                    .push((location, CallTarget::Method(name, receiver)));
+                self.visit_arguments(args);
+            }
+            Follow::Index(_, expr) => self.visit_expression(expr),
+            _ => {}
+        }
+    }
+
+    fn visit_arguments(&mut self, args: &'o [Expression]) {
+        for arg in args.iter() {
+            self.visit_expression(arg);
+        }
+    }
+}
+
+/// Add every override of `name` which a call on a value of type `ty` might
+/// run: those it inherits, and those on any subtype it might really be.
+fn push_dispatch(out: &mut Vec<(String, usize)>, ty: TypeRef, name: &str) {
+    for parent in ty.iter_parent_types().skip(1) {
+        push_overrides(out, parent, name);
+    }
+    ty.recurse(&mut |child| push_overrides(out, child, name));
+}
+
+/// Convert an object tree proc reference to its debugger equivalent.
+fn debug_proc_ref(proc: ProcRef) -> Option<(String, usize)> {
+    let proc_ref = format!("{}/{}", proc.ty().path, proc.name());
+    let skip = builtin_prefix(&proc_ref, &proc.ty().get().procs[proc.name()].value);
+    let override_id = proc.index().checked_sub(skip)?;
+    Some((proc_ref, override_id))
+}
+
+impl Debugger {
+    /// Determine the proc, override, and line currently executing in a frame.
+    fn frame_location(&mut self, frame_id: i64) -> Result<(String, usize, i64), Box<dyn Error>> {
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                let extools = extools.get()?;
+                let (thread, frame_no) = extools.get_thread_by_frame_id(frame_id)?;
+                let Some(frame) = thread.call_stack.get(frame_no) else {
+                    return Err(Box::new(GenericError("Stack frame out of range")));
+                };
+                let Some(line) =
+                    extools.offset_to_line(&frame.proc, frame.override_id, frame.offset)
+                else {
+                    return Err(Box::new(GenericError("Unable to determine current line")));
+                };
+                Ok((frame.proc.clone(), frame.override_id, line))
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                let Some(instruction) = auxtools.get_current_instruction(frame_id as u32)? else {
+                    return Err(Box::new(GenericError(
+                        "Unable to determine current instruction",
+                    )));
+                };
+                let Some(line) = auxtools.get_line_number(
+                    &instruction.proc.path,
+                    instruction.proc.override_id,
+                    instruction.offset,
+                )?
+                else {
+                    return Err(Box::new(GenericError("Unable to determine current line")));
+                };
+                Ok((
+                    instruction.proc.path,
+                    instruction.proc.override_id as usize,
+                    line as i64,
+                ))
+            }
+        }
+    }
+
+    /// List the calls made on the current line of a frame, and remember which
+    /// procs each of them might enter for a later `stepIn`.
+    pub fn step_in_targets(
+        &mut self,
+        frame_id: i64,
+    ) -> Result<StepInTargetsResponse, Box<dyn Error>> {
+        let (proc_path, override_id, line) = self.frame_location(frame_id)?;
+
+        self.step_in_targets.clear();
+        let mut targets = Vec::new();
+
+        let objtree = &*self.db.objtree;
+        let Some((ty, proc_name)) = split_proc_ref(objtree, &proc_path) else {
+            return Ok(StepInTargetsResponse { targets });
+        };
+        let Some(proc) = get_proc(objtree, &proc_path, override_id) else {
+            return Ok(StepInTargetsResponse { targets });
+        };
+        let Some(code) = proc.code.as_ref() else {
+            return Ok(StepInTargetsResponse { targets });
+        };
+
+        let mut collector = CallCollector::new(objtree, ty, proc, line as u32);
+        collector.visit_block(code);
+
+        for (location, target) in collector.found {
+            let mut procs = Vec::new();
+            let mut plain_step = false;
+            let label = match target {
+                CallTarget::Unscoped(name) => {
+                    if ty.get_proc(name).is_some() {
+                        push_dispatch(&mut procs, ty, name);
+                    } else {
+                        push_overrides(&mut procs, objtree.root(), name);
+                    }
+                    format!("{}()", name)
+                }
+                CallTarget::Global(name) => {
+                    push_overrides(&mut procs, objtree.root(), name);
+                    format!("global.{}()", name)
+                }
+                CallTarget::Method(name, Some(receiver)) if receiver.get_proc(name).is_some() => {
+                    push_dispatch(&mut procs, receiver, name);
+                    format!(".{}()", name)
+                }
+                CallTarget::Method(name, _) => {
+                    // The receiver's type is unknown. Arming every override
+                    // in the tree would be far too slow, so this target is a
+                    // plain step in, with no procs.
+                    plain_step = true;
+                    format!(".{}()", name)
+                }
+                CallTarget::SelfCall => {
+                    procs.push((proc_path.clone(), override_id));
+                    format!(".() ({})", proc_name)
+                }
+                CallTarget::ParentCall => {
+                    if override_id > 0 {
+                        procs.push((proc_path.clone(), override_id - 1));
+                    } else if let Some(parent) = ty
+                        .parent_type()
+                        .and_then(|parent| parent.get_proc(proc_name))
+                    {
+                        procs.extend(debug_proc_ref(parent));
+                    }
+                    format!("..() ({})", proc_name)
+                }
+                CallTarget::New(prefab) => {
+                    if let Some(new_proc) = ty
+                        .navigate_path(&prefab.path)
+                        .and_then(|nav| nav.ty().get_proc("New"))
+                    {
+                        procs.extend(debug_proc_ref(new_proc));
+                    }
+                    format!("new {}()", FormatTypePath(&prefab.path))
+                }
+            };
+
+            if procs.is_empty() && !plain_step {
+                continue;
+            }
+
+            self.step_in_targets.push(procs);
+            targets.push(StepInTarget {
+                id: self.step_in_targets.len() as i64,
+                label,
+                line: Some(i64::from(location.line)),
+                column: Some(i64::from(location.column)),
+                ..Default::default()
+            });
+        }
+
+        Ok(StepInTargetsResponse { targets })
+    }
+
+    /// Step over the current line, stopping early if the chosen call is made.
+    pub fn step_in_target(&mut self, thread_id: i64, target_id: i64) -> Result<(), Box<dyn Error>> {
+        let Some(procs) = usize::try_from(target_id - 1)
+            .ok()
+            .and_then(|idx| self.step_in_targets.get(idx))
+        else {
+            return Err(Box::new(GenericError("Unknown step-in target")));
+        };
+
+        if procs.is_empty() {
+            self.notify_continue();
+            match &mut self.client {
+                DebugClient::Extools(extools) => {
+                    extools.get()?.step_in(thread_id);
+                }
+
+                DebugClient::Auxtools(auxtools) => {
+                    auxtools.step_into(thread_id as u32)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let breakpoints = procs
+            .iter()
+            .map(|(proc, override_id)| (proc.clone(), *override_id, 0))
+            .collect();
+        self.set_one_shot_breakpoints(breakpoints)?;
+
+        self.notify_continue();
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                extools.get()?.step_over(thread_id);
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                auxtools.next(thread_id as u32)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the instruction at which "run to cursor" should stop.
+    pub fn goto_targets(
+        &mut self,
+        params: GotoTargetsArguments,
+    ) -> Result<GotoTargetsResponse, Box<dyn Error>> {
+        self.goto_targets.clear();
+
+        let Some(file_path) = params.source.path else {
+            return Err(Box::new(GenericError("missing .source.path")));
+        };
+        let Some(file_id) = self.db.file_id(&file_path) else {
+            return Err(Box::new(GenericError("file is not part of environment")));
+        };
+        let Some((typepath, name, override_id)) =
+            self.db.location_to_proc_ref(file_id, params.line)
+        else {
+            return Ok(GotoTargetsResponse {
+                targets: Vec::new(),
+            });
+        };
+        let proc = format!("{}/{}", typepath, name);
+
+        let offset = match &mut self.client {
+            DebugClient::Extools(extools) => {
+                extools
+                    .get()?
+                    .line_to_offset(&proc, override_id, params.line)
+            }
+
+            DebugClient::Auxtools(auxtools) => auxtools
+                .get_offset(&proc, override_id as u32, params.line as u32)?
+                .map(i64::from),
+        };
+
+        let Some(offset) = offset else {
+            return Ok(GotoTargetsResponse {
+                targets: Vec::new(),
+            });
+        };
+
+        let instruction_pointer = format!("{}#{}@{}", proc, override_id, offset);
+        self.goto_targets.push((proc, override_id, offset));
+        Ok(GotoTargetsResponse {
+            targets: vec![GotoTarget {
+                id: self.goto_targets.len() as i64,
+                label: format!("Run to line {}", params.line),
+                line: params.line,
+                instructionPointerReference: Some(instruction_pointer),
+                ..Default::default()
+            }],
+        })
+    }
+
+    /// Resume execution until the chosen goto target is reached.
+    ///
+    /// DM can't move the instruction pointer, so this runs to the target
+    /// rather than jumping to it.
+    pub fn run_to_target(&mut self, target_id: i64) -> Result<(), Box<dyn Error>> {
+        let Some(target) = usize::try_from(target_id - 1)
+            .ok()
+            .and_then(|idx| self.goto_targets.get(idx))
+        else {
+            return Err(Box::new(GenericError("Unknown goto target")));
+        };
+
+        self.set_one_shot_breakpoints(vec![target.clone()])?;
+
+        self.notify_continue();
+        match &mut self.client {
+            DebugClient::Extools(extools) => {
+                extools.get()?.continue_execution();
+            }
+
+            DebugClient::Auxtools(auxtools) => {
+                auxtools.continue_execution()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_user_breakpoint(&self, tup: &(String, usize, i64)) -> bool {
+        self.saved_breakpoints
+            .values()
+            .any(|saved| saved.contains(tup))
+    }
+
+    /// Set breakpoints which are removed the next time the debuggee stops.
+    fn set_one_shot_breakpoints(
+        &mut self,
+        breakpoints: Vec<(String, usize, i64)>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.clear_one_shot_breakpoints();
+
+        for tup in breakpoints {
+            // Leave the user's own breakpoints alone.
+            if self.is_user_breakpoint(&tup) {
+                continue;
+            }
+
+            match &mut self.client {
+                DebugClient::Extools(extools) => {
+                    extools.get()?.set_breakpoint(&tup.0, tup.1, tup.2);
+                }
+
+                DebugClient::Auxtools(auxtools) => {
+                    auxtools.set_breakpoint(
+                        auxtools_types::InstructionRef {
+                            proc: auxtools_types::ProcRef {
+                                path: tup.0.clone(),
+                                override_id: tup.1 as u32,
+                            },
+                            offset: tup.2 as u32,
+                        },
+                        None,
+                    )?;
+                }
+            }
+            // Recorded one at a time, so that if setting a later one fails
+            // these are still removed. The connection's reader thread removes
+            // them as soon as it sees the debuggee stop.
+            self.seq.one_shot_breakpoints().push(tup);
+        }
+        Ok(())
+    }
+
+    /// Stop treating breakpoints the user has since set at the same place as
+    /// one-shot, so that stopping doesn't remove them.
+    pub fn release_user_breakpoints(&mut self) {
+        let seq = self.seq.clone();
+        seq.one_shot_breakpoints()
+            .retain(|tup| !self.is_user_breakpoint(tup));
+    }
+
+    pub fn clear_one_shot_breakpoints(&mut self) {
+        for tup in self.seq.take_one_shot_breakpoints() {
+            // The user may have set a real breakpoint here in the meantime.
+            if self.is_user_breakpoint(&tup) {
+                continue;
+            }
+
+            match &mut self.client {
+                DebugClient::Extools(extools) => {
+                    if let Some(extools) = extools.as_ref() {
+                        extools.unset_breakpoint(&tup.0, tup.1, tup.2);
+                    }
+                }
+
+                DebugClient::Auxtools(auxtools) => {
+                    let _ = auxtools.unset_breakpoint(&auxtools_types::InstructionRef {
+                        proc: auxtools_types::ProcRef {
+                            path: tup.0,
+                            override_id: tup.1 as u32,
+                        },
+                        offset: tup.2 as u32,
+                    });
+                }
+            }
+        }
+    }
+}
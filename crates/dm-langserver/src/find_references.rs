@@ -128,6 +128,7 @@ enum StaticType<'o> {
     List {
         list: TypeRef<'o>,
         keys: Box<StaticType<'o>>,
+        values: Box<StaticType<'o>>,
     },
 }
 
@@ -455,9 +456,10 @@ impl<'o> WalkProc<'o> {
         match ty {
             StaticType::None => {}
             StaticType::Type(ty) => self.tab.use_symbol(ty.id, location),
-            StaticType::List { list, keys } => {
+            StaticType::List { list, keys, values } => {
                 self.tab.use_symbol(list.id, location);
                 self.use_type(location, keys);
+                self.use_type(location, values);
             }
         }
     }
@@ -599,6 +601,7 @@ impl<'o> WalkProc<'o> {
                 StaticType::List {
                     list: self.objtree.expect("/list"),
                     keys: Box::new(StaticType::None),
+                    values: Box::new(StaticType::None),
                 }
             }
             Term::Locate { args, in_list } => {
@@ -895,6 +898,7 @@ impl<'o> WalkProc<'o> {
                 "SpacemanDMM_final",
                 "SpacemanDMM_private",
                 "SpacemanDMM_protected",
+                "SpacemanDMM_deprecated",
             ]
             .contains(&&*of[0])
         {
@@ -904,10 +908,14 @@ impl<'o> WalkProc<'o> {
         if of.is_empty() {
             StaticType::None
         } else if of[0] == "list" {
-            let keys = self.static_type(location, &of[1..]);
+            let (keys, values) = match of.iter().position(|each| each == "SpacemanDMM_list_of") {
+                Some(idx) => (&of[1..idx], &of[idx + 1..]),
+                None => (&of[1..], &[][..]),
+            };
             StaticType::List {
                 list: self.objtree.expect("/list"),
-                keys: Box::new(keys),
+                keys: Box::new(self.static_type(location, keys)),
+                values: Box::new(self.static_type(location, values)),
             }
         } else if let Some(ty) = self.objtree.type_by_path(of) {
             StaticType::Type(ty)
@@ -32,7 +32,7 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, mpsc};
 
 use jsonrpc::{Call, Output, Request, Response};
 use lsp_types::MessageType;
@@ -191,6 +191,7 @@ impl DiagnosticsTracker {
                 range: location_to_range(loc),
                 source: component_to_source(error.component()),
                 code: convert_errorcode(error.errortype()),
+                tags: convert_tags(error.errortype()),
                 related_information,
                 ..Default::default()
             };
@@ -254,6 +255,7 @@ struct Engine<'a> {
     status: InitStatus,
     parent_pid: u32,
     threads: Vec<std::thread::JoinHandle<()>>,
+    debuggers: Vec<mpsc::Sender<debugger::DebugDatabaseBuilder>>,
     root: Option<Url>,
 
     context: &'a dm::Context,
@@ -277,6 +279,7 @@ impl<'a> Engine<'a> {
             status: InitStatus::Starting,
             parent_pid: 0,
             threads: Default::default(),
+            debuggers: Default::default(),
             root: None,
 
             context,
@@ -450,6 +453,20 @@ impl<'a> Engine<'a> {
     // ------------------------------------------------------------------------
     // Environment tracking
 
+    fn debug_database(&self) -> Result<debugger::DebugDatabaseBuilder, jsonrpc::Error> {
+        let root_dir = match self.root.as_ref() {
+            Some(url) => url_to_path(url)?,
+            None => Default::default(),
+        };
+        Ok(debugger::DebugDatabaseBuilder {
+            root_dir,
+            files: self.context.clone_file_list(),
+            objtree: self.objtree.clone(),
+            extools_dll: self.extools_dll.clone(),
+            debug_server_dll: self.debug_server_dll.clone(),
+        })
+    }
+
     fn parse_environment(&mut self, environment: PathBuf) -> Result<(), jsonrpc::Error> {
         // handle the parsing
         let original_start = std::time::Instant::now();
@@ -515,6 +532,12 @@ impl<'a> Engine<'a> {
             fatal_errored = fatal_errored_2;
             self.objtree = Arc::new(objtree);
         }
+        if !self.debuggers.is_empty()
+            && let Ok(db) = self.debug_database()
+        {
+            // Running debuggers want the new tree for their function breakpoints.
+            self.debuggers.retain(|tx| tx.send(db.clone()).is_ok());
+        }
         let elapsed = start.elapsed();
         start += elapsed;
         {
@@ -556,6 +579,7 @@ impl<'a> Engine<'a> {
             let diagnostics_tracker = self.diagnostics_tracker.clone();
             std::thread::spawn(move || {
                 dreamchecker::run(&context, &objtree);
+                context.check_unused_suppressions();
                 let elapsed = start.elapsed();
                 start += elapsed;
                 eprint!(
@@ -684,6 +708,7 @@ impl<'a> Engine<'a> {
                     self.context
                         .errors_mut()
                         .retain(|error| error.location().file != file_id);
+                    self.context.clear_suppressions(file_id);
 
                     pp.enable_annotations();
                     let mut annotations = AnnotationTree::default();
@@ -698,6 +723,7 @@ impl<'a> Engine<'a> {
                     }
                     pp.finalize();
                     dreamchecker::run(self.context, &self.objtree);
+                    self.context.check_unused_suppressions();
 
                     // Perform a diagnostics pump on this file only.
                     // Assume all errors are in this file.
@@ -730,6 +756,7 @@ impl<'a> Engine<'a> {
                             range: location_to_range(loc),
                             source: component_to_source(error.component()),
                             code: convert_errorcode(error.errortype()),
+                            tags: convert_tags(error.errortype()),
                             related_information,
                             ..Default::default()
                         };
@@ -2133,19 +2160,11 @@ handle_method_call! {
     // ------------------------------------------------------------------------
     // debugger entry point
     on StartDebugger(&mut self, params) {
-        let root_dir = match self.root.as_ref() {
-            Some(url) => url_to_path(url)?,
-            None => Default::default(),
-        };
-        let db = debugger::DebugDatabaseBuilder {
-            root_dir,
-            files: self.context.clone_file_list(),
-            objtree: self.objtree.clone(),
-            extools_dll: self.extools_dll.clone(),
-            debug_server_dll: self.debug_server_dll.clone(),
-        };
-        let (port, handle) = debugger::start_server(self.context.config().debugger.engine, params.dreamseeker_exe, db).map_err(invalid_request)?;
+        let db = self.debug_database()?;
+        let (db_tx, db_rx) = mpsc::channel();
+        let (port, handle) = debugger::start_server(self.context.config().debugger.clone(), params.dreamseeker_exe, db, db_rx).map_err(invalid_request)?;
         self.threads.push(handle);
+        self.debuggers.push(db_tx);
         extras::StartDebuggerResult { port }
     }
 }
@@ -2264,6 +2283,13 @@ fn convert_errorcode(errortype: Option<&'static str>) -> Option<lsp_types::Numbe
     errortype.map(|x| lsp_types::NumberOrString::String(x.to_owned()))
 }
 
+fn convert_tags(errortype: Option<&'static str>) -> Option<Vec<lsp_types::DiagnosticTag>> {
+    match errortype {
+        Some("deprecated") => Some(vec![lsp_types::DiagnosticTag::DEPRECATED]),
+        _ => None,
+    }
+}
+
 enum UnscopedVar<'a> {
     Parameter {
         ty: TypeRef<'a>,
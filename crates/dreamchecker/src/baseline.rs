@@ -0,0 +1,220 @@
+//! Baselines of known diagnostics, so that only new findings are reported.
+//!
+//! Each diagnostic is fingerprinted by its errortype, the proc or type it
+//! appears in, and its message with numbers removed. Line numbers and file
+//! names are deliberately left out so that moving code around doesn't
+//! invalidate the baseline.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use ahash::RandomState;
+use dreammaker::objtree::ObjectTree;
+use dreammaker::{DMError, FileId, Location};
+
+/// Finds the definition enclosing a given location.
+pub struct Enclosing {
+    by_file: HashMap<FileId, Vec<(Location, String)>, RandomState>,
+}
+
+impl Enclosing {
+    pub fn new(objtree: &ObjectTree) -> Enclosing {
+        let mut by_file: HashMap<FileId, Vec<(Location, String)>, RandomState> = Default::default();
+        let mut add = |location: Location, label: String| {
+            if !location.is_builtins() {
+                by_file
+                    .entry(location.file)
+                    .or_default()
+                    .push((location, label));
+            }
+        };
+
+        for ty in objtree.iter_types() {
+            add(ty.location, ty.path.clone());
+            for var in ty.vars.values() {
+                add(var.value.location, ty.path.clone());
+            }
+            for (name, proc) in ty.procs.iter() {
+                for value in proc.value.iter() {
+                    add(value.location, format!("{}/proc/{}", ty.path, name));
+                }
+            }
+        }
+
+        for definitions in by_file.values_mut() {
+            definitions.sort();
+        }
+        Enclosing { by_file }
+    }
+
+    /// The closest proc or type defined at or before `location`.
+    pub fn lookup(&self, location: Location) -> &str {
+        let Some(definitions) = self.by_file.get(&location.file) else {
+            return "";
+        };
+        let idx = definitions.partition_point(|(start, _)| *start <= location);
+        match idx.checked_sub(1) {
+            Some(idx) => &definitions[idx].1,
+            None => "",
+        }
+    }
+}
+
+/// Compute the fingerprint of a diagnostic.
+pub fn fingerprint(enclosing: &Enclosing, error: &DMError) -> String {
+    format!(
+        "{}\t{}\t{}",
+        error.errortype().unwrap_or("-"),
+        enclosing.lookup(error.location()),
+        normalize_message(error.description()),
+    )
+}
+
+fn normalize_message(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut in_number = false;
+    for ch in message
+        .split_whitespace()
+        .flat_map(|word| word.chars().chain(Some(' ')))
+    {
+        if ch.is_ascii_digit() {
+            if !in_number {
+                out.push('#');
+            }
+            in_number = true;
+        } else {
+            out.push(ch);
+            in_number = false;
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// A multiset of fingerprints loaded from a baseline file.
+#[derive(Default)]
+pub struct Baseline {
+    counts: HashMap<String, usize, RandomState>,
+}
+
+impl Baseline {
+    pub fn read(path: &Path) -> io::Result<Baseline> {
+        let mut baseline = Baseline::default();
+        for line in io::BufReader::new(std::fs::File::open(path)?).lines() {
+            let line = line?;
+            if !line.is_empty() {
+                *baseline.counts.entry(line).or_default() += 1;
+            }
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(path: &Path, fingerprints: &mut [String]) -> io::Result<()> {
+        fingerprints.sort();
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        for each in fingerprints.iter() {
+            writeln!(file, "{}", each)?;
+        }
+        file.flush()
+    }
+
+    /// Remove one occurrence of `fingerprint`, returning whether it was
+    /// present.
+    pub fn take(&mut self, fingerprint: &str) -> bool {
+        match self.counts.get_mut(fingerprint) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use dreammaker::Context;
+
+    /// Parse `code` as the file `test.dm`.
+    pub fn parse(context: &Context, code: &'static str) -> (ObjectTree, FileId) {
+        let pp = dreammaker::preprocessor::Preprocessor::from_buffer(
+            context,
+            "test.dm".into(),
+            code.trim(),
+        );
+        let indents = dreammaker::indents::IndentProcessor::new(context, pp);
+        let mut parser = dreammaker::parser::Parser::new(context, indents);
+        parser.enable_procs();
+        let tree = parser.parse_object_tree();
+        let file = context.get_file(Path::new("test.dm")).unwrap();
+        (tree, file)
+    }
+
+    const CODE: &str = r#"
+/obj/door
+    var/open = 0
+
+/obj/door/proc/toggle()
+    open = !open
+
+/obj/door/airlock
+"#;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_message("dir 5 cannot  be shown,\n1.5 frames x10"),
+            "dir # cannot be shown, #.# frames x#"
+        );
+        assert_eq!(normalize_message("  no numbers "), "no numbers");
+    }
+
+    #[test]
+    fn enclosing_and_fingerprint() {
+        let context = Context::default();
+        let (tree, file) = parse(&context, CODE);
+        let enclosing = Enclosing::new(&tree);
+        let at = |line| Location {
+            file,
+            line,
+            column: u16::MAX,
+        };
+        assert_eq!(enclosing.lookup(at(2)), "/obj/door");
+        assert_eq!(enclosing.lookup(at(5)), "/obj/door/proc/toggle");
+        assert_eq!(enclosing.lookup(at(7)), "/obj/door/airlock");
+        assert_eq!(enclosing.lookup(Location::builtins()), "");
+
+        let error = DMError::new(at(5), "line 5 has 2 problems").with_errortype("door_problem");
+        assert_eq!(
+            fingerprint(&enclosing, &error),
+            "door_problem\t/obj/door/proc/toggle\tline # has # problems"
+        );
+        let moved = DMError::new(at(6), "line 6 has 3 problems").with_errortype("door_problem");
+        assert_eq!(
+            fingerprint(&enclosing, &moved),
+            fingerprint(&enclosing, &error)
+        );
+        let untyped = DMError::new(at(2), "bad");
+        assert_eq!(fingerprint(&enclosing, &untyped), "-\t/obj/door\tbad");
+    }
+
+    #[test]
+    fn multiset() {
+        let path = std::env::temp_dir().join("dreamchecker-baseline-multiset.txt");
+        let mut fingerprints = vec!["b".to_owned(), "a".to_owned(), "b".to_owned()];
+        Baseline::write(&path, &mut fingerprints).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\nb\n");
+
+        let mut baseline = Baseline::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(baseline.take("b"));
+        assert!(baseline.take("a"));
+        assert!(baseline.take("b"));
+        // Each entry matches only as many diagnostics as it was recorded for.
+        assert!(!baseline.take("b"));
+        assert!(!baseline.take("a"));
+        assert!(!baseline.take("c"));
+    }
+}
@@ -0,0 +1,180 @@
+//! Restricting diagnostics to the lines changed since a git revision, so that
+//! a pull request is only held responsible for what it introduced.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ahash::RandomState;
+use dreammaker::{Context, DMError, FileId, Location};
+use git2::{DiffOptions, Repository};
+
+use crate::baseline::Enclosing;
+
+/// Diagnostics about a chain of calls, which a change to any proc along the
+/// chain could have caused.
+const CALL_TREE_ERRORTYPES: &[&str] = &["must_not_sleep", "must_be_pure"];
+
+pub struct Changes {
+    /// Inclusive ranges of changed lines in each file.
+    lines: HashMap<FileId, Vec<(u32, u32)>, RandomState>,
+    /// Procs and types, as labelled by `Enclosing`, with a changed line.
+    definitions: HashSet<String, RandomState>,
+}
+
+impl Changes {
+    /// Compare the working tree and index to `rev`. Files which are not
+    /// tracked yet count as entirely changed.
+    pub fn since(
+        context: &Context,
+        enclosing: &Enclosing,
+        environment: &Path,
+        rev: &str,
+    ) -> Result<Changes, git2::Error> {
+        let env_dir = match environment.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let repo = Repository::discover(env_dir)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("repository has no working directory"))?;
+        let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+
+        let mut options = DiffOptions::new();
+        options
+            .context_lines(0)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+        let mut by_path: HashMap<PathBuf, Vec<(u32, u32)>, RandomState> = Default::default();
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            Some(&mut |delta, hunk| {
+                if let Some(path) = delta.new_file().path() {
+                    let start = hunk.new_start();
+                    // A deletion is reported as the line before it.
+                    let range = match hunk.new_lines() {
+                        0 => (start.max(1), start + 1),
+                        count => (start, start + count - 1),
+                    };
+                    by_path.entry(workdir.join(path)).or_default().push(range);
+                }
+                true
+            }),
+            None,
+        )?;
+        let by_path: HashMap<PathBuf, Vec<(u32, u32)>, RandomState> = by_path
+            .into_iter()
+            .filter_map(|(path, ranges)| Some((path.canonicalize().ok()?, ranges)))
+            .collect();
+
+        let mut lines: HashMap<FileId, Vec<(u32, u32)>, RandomState> = Default::default();
+        context.file_list().for_each(|path| {
+            if let Ok(absolute) = env_dir.join(path).canonicalize()
+                && let Some(ranges) = by_path.get(&absolute)
+                && let Some(file) = context.get_file(path)
+            {
+                lines.insert(file, ranges.clone());
+            }
+        });
+
+        let mut definitions: HashSet<String, RandomState> = Default::default();
+        for (&file, ranges) in lines.iter() {
+            for &(start, end) in ranges.iter() {
+                for line in start..=end {
+                    let label = enclosing.lookup(Location {
+                        file,
+                        line,
+                        column: u16::MAX,
+                    });
+                    if !label.is_empty() && !definitions.contains(label) {
+                        definitions.insert(label.to_owned());
+                    }
+                }
+            }
+        }
+
+        Ok(Changes { lines, definitions })
+    }
+
+    fn is_changed(&self, location: Location) -> bool {
+        self.lines.get(&location.file).is_some_and(|ranges| {
+            ranges
+                .iter()
+                .any(|&(start, end)| start <= location.line && location.line <= end)
+        })
+    }
+
+    /// Whether a diagnostic is on a changed line or, for call-tree
+    /// diagnostics, passes through a proc which changed.
+    pub fn is_relevant(&self, enclosing: &Enclosing, error: &DMError) -> bool {
+        if self.is_changed(error.location()) {
+            return true;
+        }
+        error
+            .errortype()
+            .is_some_and(|errortype| CALL_TREE_ERRORTYPES.contains(&errortype))
+            && std::iter::once(error.location())
+                .chain(error.notes().iter().map(|note| note.location()))
+                .any(|location| self.definitions.contains(enclosing.lookup(location)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::tests::parse;
+
+    const CODE: &str = r#"
+/proc/helper()
+    sleep(1)
+
+/proc/caller()
+    set waitfor = 0
+    helper()
+
+/proc/unrelated()
+    world.log << "hi"
+"#;
+
+    #[test]
+    fn relevance() {
+        let context = Context::default();
+        let (tree, file) = parse(&context, CODE);
+        let enclosing = Enclosing::new(&tree);
+        let at = |line| Location {
+            file,
+            line,
+            column: u16::MAX,
+        };
+        // Only the body of `helper` changed.
+        let mut lines: HashMap<FileId, Vec<(u32, u32)>, RandomState> = Default::default();
+        lines.insert(file, vec![(2, 2)]);
+        let changes = Changes {
+            lines,
+            definitions: ["/proc/helper".to_owned()].into_iter().collect(),
+        };
+
+        let on_changed = DMError::new(at(2), "changed").with_errortype("unrelated");
+        assert!(changes.is_relevant(&enclosing, &on_changed));
+        let elsewhere = DMError::new(at(9), "unchanged").with_errortype("unrelated");
+        assert!(!changes.is_relevant(&enclosing, &elsewhere));
+
+        // A call-tree diagnostic on an unchanged proc still counts if a proc
+        // it passes through changed.
+        let call_tree = |errortype| {
+            DMError::new(at(6), "caller sleeps")
+                .with_errortype(errortype)
+                .with_note(at(2), "sleep called here")
+        };
+        assert!(changes.is_relevant(&enclosing, &call_tree("must_not_sleep")));
+        assert!(!changes.is_relevant(&enclosing, &call_tree("other")));
+        let untouched = DMError::new(at(6), "caller sleeps")
+            .with_errortype("must_not_sleep")
+            .with_note(at(9), "unrelated here");
+        assert!(!changes.is_relevant(&enclosing, &untouched));
+    }
+}
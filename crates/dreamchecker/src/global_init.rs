@@ -0,0 +1,227 @@
+//! Checking the order in which global vars are initialized.
+//!
+//! Global vars whose values aren't compile-time constants are initialized
+//! when the world starts, in the order they were declared. An initializer
+//! which reads a global declared later, or which depends on itself through
+//! other globals, sees null instead.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ahash::RandomState;
+use dm::ast::*;
+use dm::constants::Constant;
+use dm::objtree::ObjectTree;
+use dm::{Context, DMError, Location, Severity};
+use dreammaker as dm;
+
+use crate::walk::{Walker, walk_expression};
+
+/// A global var with an initializer.
+struct Global<'o> {
+    name: &'o str,
+    location: Location,
+    /// Whether the initializer runs at world startup, rather than being
+    /// known at compile time.
+    runtime: bool,
+    /// The globals the initializer reads, and where.
+    reads: Vec<(usize, Location)>,
+}
+
+/// Collects the reads of globals in an initializer.
+struct GlobalReads<'a> {
+    indices: &'a HashMap<&'a str, usize, RandomState>,
+    reads: Vec<(usize, Location)>,
+}
+
+impl GlobalReads<'_> {
+    fn read(&mut self, name: &str, location: Location) {
+        if let Some(&idx) = self.indices.get(name) {
+            self.reads.push((idx, location));
+        }
+    }
+}
+
+impl Walker for GlobalReads<'_> {
+    fn expression(&mut self, expr: &Expression) {
+        // `global.name`
+        if let Expression::Base { term, follow } = expr
+            && let Term::Ident(ident) = &term.elem
+            && ident == "global"
+            && let Some(first) = follow.first()
+            && let Follow::Field(_, name) = &first.elem
+        {
+            self.read(name, first.location);
+        }
+    }
+
+    fn term(&mut self, location: Location, term: &Term) {
+        match term {
+            Term::Ident(name) => self.read(name, location),
+            Term::GlobalIdent(name) => self.read(name, location),
+            _ => {}
+        }
+    }
+}
+
+/// Check the initializers of global vars for reads of globals which have
+/// not been initialized yet, and for globals which depend on themselves.
+pub fn check_global_init_order(objtree: &ObjectTree, context: &Context) {
+    let root = objtree.root();
+    let mut declared: Vec<(&str, &Expression, Location, bool)> = Vec::new();
+    for (name, var) in root.vars.iter() {
+        if var.value.location.is_builtins() {
+            continue;
+        }
+        let (Some(decl), Some(expr)) = (root.get_var_declaration(name), &var.value.expression)
+        else {
+            continue;
+        };
+        let runtime = !decl.var_type.flags.is_const() && !is_compile_time(expr, var.value.location);
+        declared.push((name.as_str(), expr, var.value.location, runtime));
+    }
+    declared.sort_by_key(|&(name, _, location, _)| (location, name));
+
+    let indices: HashMap<&str, usize, RandomState> = declared
+        .iter()
+        .enumerate()
+        .map(|(idx, &(name, ..))| (name, idx))
+        .collect();
+    let mut globals: Vec<Global> = Vec::with_capacity(declared.len());
+    for &(name, expr, location, runtime) in declared.iter() {
+        let mut walker = GlobalReads {
+            indices: &indices,
+            reads: Vec::new(),
+        };
+        if runtime {
+            walk_expression(&mut walker, expr);
+        }
+        // Reading a compile-time constant is fine whenever it happens.
+        walker.reads.retain(|&(idx, _)| declared[idx].3);
+        globals.push(Global {
+            name,
+            location,
+            runtime,
+            reads: walker.reads,
+        });
+    }
+
+    let cyclic = check_cycles(&globals, context);
+
+    for (idx, global) in globals.iter().enumerate() {
+        for &(read, location) in global.reads.iter() {
+            if read <= idx || cyclic.contains(&(idx, read)) {
+                continue;
+            }
+            let later = &globals[read];
+            DMError::new(
+                location,
+                format!(
+                    "global var {} reads {} before it is initialized",
+                    global.name, later.name
+                ),
+            )
+            .with_component(dm::Component::DreamChecker)
+            .set_severity(Severity::Warning)
+            .with_errortype("global_init_order")
+            .with_note(
+                global.location,
+                format!("{} is initialized here", global.name),
+            )
+            .with_note(
+                later.location,
+                format!("{} is initialized later, here", later.name),
+            )
+            .register(context);
+        }
+    }
+}
+
+/// Report each cycle of globals whose initializers read each other once,
+/// returning the reads which take part in one.
+fn check_cycles(globals: &[Global], context: &Context) -> HashSet<(usize, usize)> {
+    let mut reported = HashSet::<Vec<usize>>::new();
+    let mut cyclic = HashSet::new();
+    for (start, global) in globals.iter().enumerate() {
+        if !global.runtime {
+            continue;
+        }
+
+        // The shortest way back around to `start`, found breadth-first.
+        let mut came_from = HashMap::<usize, (usize, Location)>::new();
+        let mut to_visit = VecDeque::from([start]);
+        let mut closed_at = None;
+        'search: while let Some(idx) = to_visit.pop_front() {
+            for &(read, location) in globals[idx].reads.iter() {
+                if read == start {
+                    closed_at = Some((idx, location));
+                    break 'search;
+                }
+                if let Entry::Vacant(entry) = came_from.entry(read) {
+                    entry.insert((idx, location));
+                    to_visit.push_back(read);
+                }
+            }
+        }
+        let Some((mut idx, location)) = closed_at else {
+            continue;
+        };
+        let mut path = vec![(idx, start, location)];
+        while idx != start {
+            let (reader, location) = came_from[&idx];
+            path.push((reader, idx, location));
+            idx = reader;
+        }
+        path.reverse();
+
+        let mut members: Vec<usize> = path.iter().map(|&(reader, ..)| reader).collect();
+        members.sort_unstable();
+        for &(reader, read, _) in path.iter() {
+            cyclic.insert((reader, read));
+        }
+        if !reported.insert(members) {
+            continue;
+        }
+
+        let message = if path.len() == 1 {
+            format!(
+                "global var {} reads itself while it is initialized",
+                global.name
+            )
+        } else {
+            let others: Vec<&str> = path[1..]
+                .iter()
+                .map(|&(reader, ..)| globals[reader].name)
+                .collect();
+            format!(
+                "global var {} is initialized from {}, which depends on it",
+                global.name,
+                others.join(", ")
+            )
+        };
+        let mut error = DMError::new(global.location, message)
+            .with_component(dm::Component::DreamChecker)
+            .set_severity(Severity::Warning)
+            .with_errortype("global_init_cycle");
+        for &(reader, read, location) in path.iter() {
+            error = error.with_note(
+                location,
+                format!("{} reads {} here", globals[reader].name, globals[read].name),
+            );
+        }
+        error.register(context);
+    }
+    cyclic
+}
+
+/// Whether DM can work out the value of an initializer when compiling.
+fn is_compile_time(expr: &Expression, location: Location) -> bool {
+    matches!(
+        expr.to_owned().simple_evaluate(location),
+        Ok(Constant::Null(_)
+            | Constant::Float(_)
+            | Constant::String(_)
+            | Constant::Prefab(_)
+            | Constant::Resource(_))
+    )
+}
@@ -0,0 +1,322 @@
+//! Validation of icon states against the `.dmi` files they refer to.
+//!
+//! Type vars and literal `icon_state = "..."` assignments in proc bodies are
+//! checked for states which do not exist and for `dir` values which the state
+//! cannot display. Optionally, states which nothing refers to are reported.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use ahash::RandomState;
+use dm::ast::*;
+use dm::constants::Constant;
+use dm::objtree::{ObjectTree, TypeRef};
+use dm::{Context, DMError, Location, Severity};
+use dreammaker as dm;
+use tinydmi::prelude::{Dirs, Metadata};
+
+use crate::walk::{Walker, walk_block};
+
+const SOUTH: i32 = 2;
+const DIAGONALS: &[i32] = &[5, 6, 9, 10];
+
+/// Check icon states in `objtree`, resolving `.dmi` paths against
+/// `icons_root`, which is normally the directory containing the `.dme`.
+pub fn check_icons(context: &Context, objtree: &ObjectTree, icons_root: &Path) {
+    let mut checker = IconChecker {
+        context,
+        icons_root,
+        icons: Default::default(),
+        first_use: Default::default(),
+        references: Default::default(),
+        proc_type: None,
+        proc_icon: None,
+    };
+
+    for ty in objtree.iter_types() {
+        checker.check_type(ty);
+        for var in ty.vars.values() {
+            if let Some(constant) = var.value.constant.as_ref() {
+                checker.references.add_constant(constant);
+            }
+        }
+        checker.proc_type = Some(ty);
+        checker.proc_icon = None;
+        for proc in ty.procs.values() {
+            for value in proc.value.iter() {
+                if let Some(code) = value.code.as_ref() {
+                    walk_block(&mut checker, code);
+                }
+            }
+        }
+    }
+
+    checker.check_unreferenced();
+}
+
+fn error<S: Into<String>>(location: Location, desc: S) -> DMError {
+    DMError::new(location, desc).with_component(dm::Component::DreamChecker)
+}
+
+/// Read the metadata of a `.dmi` file from its `Description` text chunk.
+fn read_metadata(path: &Path) -> Result<Metadata, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let decoder = png::Decoder::new(std::io::BufReader::new(file));
+    let reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let chunk = reader
+        .info()
+        .compressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == "Description")
+        .ok_or("no DMI description chunk")?;
+    let text = chunk.get_text().map_err(|e| e.to_string())?;
+    tinydmi::parse(text).map_err(|e| e.to_string())
+}
+
+/// String literals which might name an icon state.
+#[derive(Default)]
+struct References {
+    strings: HashSet<String, RandomState>,
+    /// The leading and trailing literal parts of interpolated strings.
+    patterns: Vec<(String, String)>,
+}
+
+impl References {
+    fn add_constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::String(text) => {
+                self.strings.insert(text.to_string());
+            }
+            Constant::List(args) | Constant::Call(_, args) => {
+                for (key, value) in args.iter() {
+                    self.add_constant(key);
+                    if let Some(value) = value {
+                        self.add_constant(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn contains(&self, state: &str) -> bool {
+        self.strings.contains(state)
+            || self.patterns.iter().any(|(prefix, suffix)| {
+                state.len() >= prefix.len() + suffix.len()
+                    && state.starts_with(prefix.as_str())
+                    && state.ends_with(suffix.as_str())
+            })
+    }
+}
+
+struct IconChecker<'a> {
+    context: &'a Context,
+    icons_root: &'a Path,
+    /// Loaded metadata, or `None` if the file could not be read.
+    icons: HashMap<String, Option<Metadata>, RandomState>,
+    /// Where each `.dmi` file was first referred to, in load order.
+    first_use: Vec<(String, Location)>,
+    references: References,
+    /// The type whose procs are being walked.
+    proc_type: Option<TypeRef<'a>>,
+    /// Its icon, once looked up.
+    proc_icon: Option<Option<String>>,
+}
+
+impl<'a> IconChecker<'a> {
+    /// The icon which `icon_state` assignments in the procs being walked
+    /// refer to. Procs also run on subtypes, so if any of them has another
+    /// icon there is no single answer.
+    fn proc_icon(&mut self) -> Option<String> {
+        let ty = self.proc_type?;
+        self.proc_icon
+            .get_or_insert_with(|| {
+                let icon = resource_value(ty, "icon")?;
+                let mut shared = true;
+                ty.recurse(&mut |subtype| {
+                    shared &= resource_value(subtype, "icon").as_ref() == Some(&icon);
+                });
+                shared.then_some(icon)
+            })
+            .clone()
+    }
+
+    fn metadata(&mut self, icon: &str, location: Location) -> Option<&Metadata> {
+        if !self.icons.contains_key(icon) {
+            let path = self.icons_root.join(icon);
+            let metadata = match read_metadata(&dm::fix_case(&path)) {
+                Ok(metadata) => Some(metadata),
+                Err(e) => {
+                    // Missing files are reported with other resource checks.
+                    if path.exists() {
+                        error(location, format!("unable to read icon '{}': {}", icon, e))
+                            .set_severity(Severity::Warning)
+                            .with_errortype("icon_unreadable")
+                            .register(self.context);
+                    }
+                    None
+                }
+            };
+            self.icons.insert(icon.to_owned(), metadata);
+            self.first_use.push((icon.to_owned(), location));
+        }
+        self.icons[icon].as_ref()
+    }
+
+    fn check_type(&mut self, ty: TypeRef) {
+        let declared = |name| ty.vars.get(name).map(|var| var.value.location);
+        let state_location = declared("icon_state");
+        let Some(location) = state_location.or(declared("icon")).or(declared("dir")) else {
+            return;
+        };
+        let Some(icon) = resource_value(ty, "icon") else {
+            return;
+        };
+        let Some(Constant::String(state)) =
+            ty.get_value("icon_state").and_then(|v| v.constant.as_ref())
+        else {
+            return;
+        };
+        let dir = match ty.get_value("dir").and_then(|v| v.constant.as_ref()) {
+            Some(Constant::Float(dir)) => *dir as i32,
+            _ => SOUTH,
+        };
+        self.check_state(&icon, state, Some(dir), location);
+    }
+
+    fn check_state(&mut self, icon: &str, state: &str, dir: Option<i32>, location: Location) {
+        // The empty state is a fallback which BYOND tolerates being missing.
+        if state.is_empty() {
+            return;
+        }
+        let context = self.context;
+        let Some(metadata) = self.metadata(icon, location) else {
+            return;
+        };
+        let Some((_, found)) = metadata.states.get(state).and_then(|list| list.first()) else {
+            error(
+                location,
+                format!("icon_state {:?} does not exist in '{}'", state, icon),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("icon_state_missing")
+            .register(context);
+            return;
+        };
+        let Some(dir) = dir else {
+            return;
+        };
+        let missing_dir = match found.dirs {
+            // BYOND shows a state with one direction for every dir.
+            Dirs::One => false,
+            Dirs::Four => DIAGONALS.contains(&dir),
+            Dirs::Eight => false,
+        };
+        if missing_dir {
+            error(
+                location,
+                format!(
+                    "dir {} cannot be shown by icon_state {:?} in '{}', which has {} direction(s)",
+                    dir,
+                    state,
+                    icon,
+                    found.dirs.get_num()
+                ),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("icon_state_dirs")
+            .register(context);
+        }
+    }
+
+    fn check_unreferenced(&mut self) {
+        for (icon, location) in std::mem::take(&mut self.first_use) {
+            let Some(metadata) = self.icons[&icon].as_ref() else {
+                continue;
+            };
+            let unreferenced: Vec<String> = metadata
+                .states
+                .keys()
+                .filter(|state| !state.is_empty() && !self.references.contains(state))
+                .map(|state| format!("{:?}", state))
+                .collect();
+            if !unreferenced.is_empty() {
+                error(
+                    location,
+                    format!(
+                        "'{}' has {} icon_state(s) which nothing references: {}",
+                        icon,
+                        unreferenced.len(),
+                        unreferenced.join(", ")
+                    ),
+                )
+                .set_severity(Severity::Hint)
+                .with_errortype("icon_state_unused")
+                .register(self.context);
+            }
+        }
+    }
+
+    /// Check `icon_state = "..."` and `src.icon_state = "..."`.
+    fn check_assignment(&mut self, location: Location, expr: &Expression) {
+        let Expression::AssignOp {
+            op: AssignOp::Assign,
+            lhs,
+            rhs,
+        } = expr
+        else {
+            return;
+        };
+        let Some(Term::String(state)) = rhs.as_term() else {
+            return;
+        };
+        let is_own_icon_state = match &**lhs {
+            Expression::Base { term, follow } => match (&term.elem, &follow[..]) {
+                (Term::Ident(name), []) => name == "icon_state",
+                (Term::Ident(src), [field]) => {
+                    src == "src"
+                        && matches!(&field.elem, Follow::Field(_, name) if name.as_str() == "icon_state")
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if is_own_icon_state && let Some(icon) = self.proc_icon() {
+            self.check_state(&icon, state, None, location);
+        }
+    }
+}
+
+impl Walker for IconChecker<'_> {
+    fn statement(&mut self, location: Location, statement: &Statement) {
+        if let Statement::Expr(expr) = statement {
+            self.check_assignment(location, expr);
+        }
+    }
+
+    fn term(&mut self, _: Location, term: &Term) {
+        match term {
+            Term::String(text) => {
+                self.references.strings.insert(text.clone());
+            }
+            Term::InterpString(first, parts) => {
+                // Strings with no literal text at either end could be anything.
+                let last = parts.last().map_or("", |(_, text)| text);
+                if !first.is_empty() || !last.is_empty() {
+                    self.references
+                        .patterns
+                        .push((first.to_string(), last.to_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The path of the resource a type's var is set to, if any.
+fn resource_value(ty: TypeRef, name: &str) -> Option<String> {
+    match ty.get_value(name)?.constant.as_ref()? {
+        Constant::Resource(path) => Some(path.replace('\\', "/")),
+        _ => None,
+    }
+}
@@ -3,20 +3,33 @@
 #![allow(dead_code, unused_variables)]
 
 use dm::ast::*;
+use dm::config::LintRule;
 use dm::constants::{ConstFn, Constant};
-use dm::objtree::{ObjectTree, ProcRef, TypeRef};
+use dm::objtree::{NavigatePathResult, ObjectTree, ProcRef, TypeRef, VarDeclaration};
+use dm::preprocessor::LiteralMacros;
 use dm::{Context, DMError, Location, Severity};
 use dreammaker as dm;
 
+use std::cell::Cell;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 
 use ahash::RandomState;
+use rayon::prelude::*;
 
 mod type_expr;
 use type_expr::TypeExpr;
+mod global_init;
+use global_init::check_global_init_order;
+mod switch_cases;
+use switch_cases::check_switch_cases;
 mod switch_rand_range;
 use switch_rand_range::check_switch_rand_range;
 
+pub mod icons;
+pub mod resources;
+mod walk;
+
 #[doc(hidden)] // Intended for the tests only.
 pub mod test_helpers;
 
@@ -31,6 +44,7 @@ pub enum StaticType<'o> {
     List {
         list: TypeRef<'o>,
         keys: Box<StaticType<'o>>,
+        values: Box<StaticType<'o>>,
     },
 }
 
@@ -66,6 +80,7 @@ impl<'o> StaticType<'o> {
         StaticType::List {
             list: tree.expect("/list"),
             keys: Box::new(StaticType::None),
+            values: Box::new(StaticType::None),
         }
     }
 
@@ -73,6 +88,7 @@ impl<'o> StaticType<'o> {
         StaticType::List {
             list: tree.expect("/list"),
             keys: Box::new(StaticType::Type(tree.expect(of))),
+            values: Box::new(StaticType::None),
         }
     }
 
@@ -212,6 +228,8 @@ pub struct Analysis<'o> {
     value: Option<Constant>,
     fix_hint: Option<(Location, String)>,
     is_impure: Option<bool>,
+    /// Where this value came from, if it may be null.
+    maybe_null: Option<String>,
 }
 
 impl<'o> Analysis<'o> {
@@ -222,6 +240,7 @@ impl<'o> Analysis<'o> {
             value: None,
             fix_hint: None,
             is_impure: None,
+            maybe_null: None,
         }
     }
 
@@ -232,6 +251,7 @@ impl<'o> Analysis<'o> {
             value: Some(Constant::Null(None)),
             fix_hint: None,
             is_impure: None,
+            maybe_null: None,
         }
     }
 
@@ -256,9 +276,20 @@ impl<'o> Analysis<'o> {
             value: Some(value),
             fix_hint: None,
             is_impure: None,
+            maybe_null: None,
         }
     }
 
+    /// The most specific type this value is known to have, if any.
+    fn known_type(&self) -> Option<TypeRef<'o>> {
+        self.static_ty.basic_type().or_else(|| {
+            self.aset.set.iter().find_map(|each| match *each {
+                Assumption::IsType(true, ty) => Some(ty),
+                _ => None,
+            })
+        })
+    }
+
     fn with_fix_hint<S: Into<String>>(mut self, location: Location, desc: S) -> Self {
         if location != Location::default() {
             self.fix_hint = Some((location, desc.into()));
@@ -304,6 +335,7 @@ impl<'o> From<AssumptionSet<'o>> for Analysis<'o> {
             value: None,
             fix_hint: None,
             is_impure: None,
+            maybe_null: None,
         }
     }
 }
@@ -322,6 +354,7 @@ impl<'o> From<StaticType<'o>> for Analysis<'o> {
             fix_hint: None,
             value: None,
             is_impure: None,
+            maybe_null: None,
         }
     }
 }
@@ -329,17 +362,39 @@ impl<'o> From<StaticType<'o>> for Analysis<'o> {
 // ----------------------------------------------------------------------------
 // Entry points
 
+/// Whole-program findings which are reported outside of diagnostics.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Procs which call themselves, directly or through other procs.
+    pub recursion_cycles: Vec<RecursionCycle>,
+}
+
+/// A cycle in the call graph.
+#[derive(Debug)]
+pub struct RecursionCycle {
+    /// The calls around the cycle as `(caller, callee, location)`, starting
+    /// and ending with the same proc.
+    pub calls: Vec<(String, String, Location)>,
+}
+
 /// Run DreamChecker, registering diagnostics to the context.
-pub fn run(context: &Context, objtree: &ObjectTree) {
-    run_inner(context, objtree, false)
+pub fn run(context: &Context, objtree: &ObjectTree) -> Report {
+    run_inner(context, objtree, false, false)
 }
 
 /// Run DreamChecker, registering diagnostics and printing progress to stdout.
-pub fn run_cli(context: &Context, objtree: &ObjectTree) {
-    run_inner(context, objtree, true)
+pub fn run_cli(context: &Context, objtree: &ObjectTree) -> Report {
+    run_inner(context, objtree, true, false)
+}
+
+/// Run DreamChecker, analyzing proc bodies on the global rayon thread pool.
+///
+/// Diagnostics are registered in the same order as with `run`.
+pub fn run_parallel(context: &Context, objtree: &ObjectTree, cli: bool) -> Report {
+    run_inner(context, objtree, cli, true)
 }
 
-fn run_inner(context: &Context, objtree: &ObjectTree, cli: bool) {
+fn run_inner(context: &Context, objtree: &ObjectTree, cli: bool, parallel: bool) -> Report {
     macro_rules! cli_println {
         ($($rest:tt)*) => {
             if cli { println!($($rest)*) }
@@ -350,6 +405,7 @@ fn run_inner(context: &Context, objtree: &ObjectTree, cli: bool) {
     cli_println!("Analyzing variables...\n");
 
     check_var_defs(objtree, context);
+    check_global_init_order(objtree, context);
 
     let mut analyzer = AnalyzeObjectTree::new(context, objtree);
 
@@ -365,13 +421,15 @@ fn run_inner(context: &Context, objtree: &ObjectTree, cli: bool) {
 
     cli_println!("============================================================");
     cli_println!("Analyzing proc bodies...\n");
+    let mut procs = Vec::new();
     objtree.root().recurse(&mut |ty| {
         for proc in ty.iter_self_procs() {
             if let Some(ref code) = proc.get().code {
-                analyzer.check_proc(proc, code);
+                procs.push((proc, &code[..]));
             }
         }
     });
+    analyzer.check_procs(&procs, parallel);
 
     cli_println!("============================================================");
     cli_println!("Analyzing proc override validity...\n");
@@ -387,6 +445,10 @@ fn run_inner(context: &Context, objtree: &ObjectTree, cli: bool) {
     cli_println!("============================================================");
     cli_println!("Analyzing proc call tree...\n");
     analyzer.check_proc_call_tree();
+    analyzer.check_endless_loops();
+    let recursion_cycles = analyzer.check_recursion();
+
+    Report { recursion_cycles }
 }
 
 // ----------------------------------------------------------------------------
@@ -563,6 +625,12 @@ pub struct ViolatingProcs<'o> {
 }
 
 impl<'o> ViolatingProcs<'o> {
+    fn extend(&mut self, other: ViolatingProcs<'o>) {
+        for (proc, violators) in other.violators {
+            self.violators.entry(proc).or_default().extend(violators);
+        }
+    }
+
     pub fn insert_violator(&mut self, proc: ProcRef<'o>, builtin: &str, location: Location) {
         self.violators
             .entry(proc)
@@ -590,6 +658,52 @@ impl<'o> ViolatingOverrides<'o> {
     }
 }
 
+/// The proc settings read while analyzing a proc body.
+struct ProcSettings<'o, 'a> {
+    return_type: &'a HashMap<ProcRef<'o>, TypeExpr<'o>>,
+    must_call_parent: &'a ProcDirective<'o>,
+    must_not_override: &'a ProcDirective<'o>,
+    private: &'a ProcDirective<'o>,
+    protected: &'a ProcDirective<'o>,
+    must_be_pure: &'a ProcDirective<'o>,
+    can_be_redefined: &'a ProcDirective<'o>,
+    deprecated: &'a ProcDirective<'o>,
+    deprecation_messages: &'a HashMap<ProcRef<'o>, String>,
+    literal_macros: &'a LiteralMacros,
+}
+
+/// What analyzing a proc body contributes to the call tree analysis.
+#[derive(Default)]
+struct ProcFindings<'o> {
+    call_tree: HashMap<ProcRef<'o>, Vec<(ProcRef<'o>, Location, bool)>>,
+    sleeping_procs: ViolatingProcs<'o>,
+    impure_procs: ViolatingProcs<'o>,
+    waitfor_procs: HashSet<ProcRef<'o>>,
+    used_kwargs: BTreeMap<String, KwargInfo>,
+    endless_loops: Vec<EndlessLoop<'o>>,
+}
+
+/// A loop which can't exit, and only yields if one of its calls sleeps.
+struct EndlessLoop<'o> {
+    location: Location,
+    calls: Vec<(ProcRef<'o>, bool)>,
+}
+
+/// The environment of a single `AnalyzeProc`.
+struct ProcEnv<'o, 'a> {
+    settings: &'a ProcSettings<'o, 'a>,
+    findings: ProcFindings<'o>,
+}
+
+impl<'o, 'a> ProcEnv<'o, 'a> {
+    fn new(settings: &'a ProcSettings<'o, 'a>) -> Self {
+        ProcEnv {
+            settings,
+            findings: Default::default(),
+        }
+    }
+}
+
 /// A deeper analysis of an ObjectTree
 pub struct AnalyzeObjectTree<'o> {
     context: &'o Context,
@@ -604,6 +718,10 @@ pub struct AnalyzeObjectTree<'o> {
     sleep_exempt: ProcDirective<'o>,
     must_be_pure: ProcDirective<'o>,
     can_be_redefined: ProcDirective<'o>,
+    deprecated: ProcDirective<'o>,
+    allow_recursion: ProcDirective<'o>,
+    /// The explanation given by each `SpacemanDMM_deprecated` setting.
+    deprecation_messages: HashMap<ProcRef<'o>, String>,
     // Debug(ProcRef) -> KwargInfo
     used_kwargs: BTreeMap<String, KwargInfo>,
 
@@ -616,6 +734,8 @@ pub struct AnalyzeObjectTree<'o> {
 
     sleeping_overrides: ViolatingOverrides<'o>,
     impure_overrides: ViolatingOverrides<'o>,
+
+    endless_loops: Vec<EndlessLoop<'o>>,
 }
 
 impl<'o> AnalyzeObjectTree<'o> {
@@ -653,6 +773,9 @@ impl<'o> AnalyzeObjectTree<'o> {
                 false,
                 false,
             ),
+            deprecated: ProcDirective::new("SpacemanDMM_deprecated", false, true, true),
+            allow_recursion: ProcDirective::new("SpacemanDMM_allow_recursion", true, false, true),
+            deprecation_messages: Default::default(),
             used_kwargs: Default::default(),
             call_tree: Default::default(),
             sleeping_procs: Default::default(),
@@ -660,15 +783,96 @@ impl<'o> AnalyzeObjectTree<'o> {
             waitfor_procs: Default::default(),
             sleeping_overrides: Default::default(),
             impure_overrides: Default::default(),
+            endless_loops: Default::default(),
         }
     }
 
     /// Analyze a specific proc
     pub fn check_proc(&mut self, proc: ProcRef<'o>, code: &'o [Spanned<Statement>]) {
-        self.must_not_sleep.try_copy_from_parent(proc);
-        self.must_be_pure.try_copy_from_parent(proc);
+        self.check_procs(&[(proc, code)], false)
+    }
+
+    /// Analyze many procs, on the rayon thread pool if `parallel` is set.
+    ///
+    /// Each proc is analyzed against its own `ProcEnv` and `Context`, and
+    /// the results are merged back in the order given, so diagnostics come
+    /// out the same no matter how many threads are used.
+    pub fn check_procs(
+        &mut self,
+        procs: &[(ProcRef<'o>, &'o [Spanned<Statement>])],
+        parallel: bool,
+    ) {
+        for &(proc, _) in procs {
+            self.must_not_sleep.try_copy_from_parent(proc);
+            self.must_be_pure.try_copy_from_parent(proc);
+        }
+
+        let literal_macros = self.context.literal_macros();
+        let settings = ProcSettings {
+            return_type: &self.return_type,
+            must_call_parent: &self.must_call_parent,
+            must_not_override: &self.must_not_override,
+            private: &self.private,
+            protected: &self.protected,
+            must_be_pure: &self.must_be_pure,
+            can_be_redefined: &self.can_be_redefined,
+            deprecated: &self.deprecated,
+            deprecation_messages: &self.deprecation_messages,
+            literal_macros: &literal_macros,
+        };
+        let objtree = self.objtree;
+        let config = self.context.config().clone();
+        let new_context = || Context::with_config(config.clone());
+        let analyze =
+            |context: &mut Context, &(proc, code): &(ProcRef<'o>, &'o [Spanned<Statement>])| {
+                let mut analyzer =
+                    AnalyzeProc::new(ProcEnv::new(&settings), context, objtree, proc);
+                analyzer.run(code);
+                let findings = analyzer.env.findings;
+                (findings, std::mem::take(&mut *context.errors_mut()))
+            };
+        let results: Vec<_> = if parallel {
+            procs.par_iter().map_init(new_context, analyze).collect()
+        } else {
+            let mut context = new_context();
+            procs
+                .iter()
+                .map(|each| analyze(&mut context, each))
+                .collect()
+        };
+
+        for (findings, errors) in results {
+            self.merge_findings(findings);
+            for error in errors {
+                self.context.register_error(error);
+            }
+        }
+    }
 
-        AnalyzeProc::new(self, self.context, self.objtree, proc).run(code)
+    fn merge_findings(&mut self, findings: ProcFindings<'o>) {
+        for (proc, calls) in findings.call_tree {
+            self.call_tree.entry(proc).or_default().extend(calls);
+        }
+        self.sleeping_procs.extend(findings.sleeping_procs);
+        self.impure_procs.extend(findings.impure_procs);
+        self.waitfor_procs.extend(findings.waitfor_procs);
+        self.endless_loops.extend(findings.endless_loops);
+        for (procname, info) in findings.used_kwargs {
+            let kwargs = self
+                .used_kwargs
+                .entry(procname)
+                .or_insert_with(|| KwargInfo {
+                    location: info.location,
+                    ..Default::default()
+                });
+            for (name, called_at) in info.called_at {
+                kwargs
+                    .called_at
+                    .entry(name)
+                    .and_modify(|ca| ca.others += called_at.others + 1)
+                    .or_insert(called_at);
+            }
+        }
     }
 
     #[inline]
@@ -688,6 +892,8 @@ impl<'o> AnalyzeObjectTree<'o> {
             "SpacemanDMM_allowed_to_sleep" => &mut self.sleep_exempt,
             "SpacemanDMM_should_be_pure" => &mut self.must_be_pure,
             "SpacemanDMM_can_be_redefined" => &mut self.can_be_redefined,
+            "SpacemanDMM_deprecated" => &mut self.deprecated,
+            "SpacemanDMM_allow_recursion" => &mut self.allow_recursion,
             other => {
                 error(location, format!("unknown linter setting {:?}", directive))
                     .with_errortype("unknown_linter_setting")
@@ -713,10 +919,21 @@ impl<'o> AnalyzeObjectTree<'o> {
             return;
         }
 
-        match directive_value_to_truthy(expr, location) {
+        // Deprecation takes an explanation in place of a truthy value.
+        let message = match expr.as_term() {
+            Some(Term::String(message)) if directive == "SpacemanDMM_deprecated" => Some(message),
+            _ => None,
+        };
+        let value = match message {
+            Some(_) => Ok(true),
+            None => directive_value_to_truthy(expr, location),
+        };
+        match value {
             Ok(truthy) => {
                 if let Err(error) = procdirective.insert(proc, truthy, location) {
                     self.context.register_error(error);
+                } else if let Some(message) = message {
+                    self.deprecation_messages.insert(proc, message.clone());
                 }
             }
             Err(error) => self
@@ -969,6 +1186,197 @@ impl<'o> AnalyzeObjectTree<'o> {
         }
     }
 
+    /// Report `while(TRUE)` style loops which never sleep, directly or
+    /// through any proc they call.
+    pub fn check_endless_loops(&self) {
+        if self.endless_loops.is_empty() {
+            return;
+        }
+
+        // Walk the call tree backwards from the procs which sleep. Calls
+        // may dispatch to an override, so a sleeping override also counts
+        // against the procs it overrides.
+        let mut callers = HashMap::<ProcRef<'o>, Vec<ProcRef<'o>>>::new();
+        for (caller, calls) in self.call_tree.iter() {
+            for &(callee, _, new_context) in calls.iter() {
+                if !new_context && !self.waitfor_procs.contains(&callee) {
+                    callers.entry(callee).or_default().push(*caller);
+                }
+            }
+        }
+        let mut sleeps = HashSet::<ProcRef<'o>>::new();
+        let mut to_visit: VecDeque<ProcRef<'o>> =
+            self.sleeping_procs.violators.keys().copied().collect();
+        while let Some(proc) = to_visit.pop_front() {
+            if !sleeps.insert(proc) {
+                continue;
+            }
+            if let Some(parent) = proc.parent_proc() {
+                to_visit.push_back(parent);
+            }
+            if let Some(procs) = callers.get(&proc) {
+                to_visit.extend(procs.iter().copied());
+            }
+        }
+
+        for endless in self.endless_loops.iter() {
+            let yields = endless.calls.iter().any(|&(proc, new_context)| {
+                !new_context && !self.waitfor_procs.contains(&proc) && sleeps.contains(&proc)
+            });
+            if !yields {
+                error(
+                    endless.location,
+                    "loop never yields: it has no break or return, and nothing in it sleeps",
+                )
+                .set_severity(Severity::Warning)
+                .with_errortype("loop_never_yields")
+                .register(self.context);
+            }
+        }
+    }
+
+    /// Find cycles in the call graph, reporting each one at the proc in it
+    /// which is defined first.
+    pub fn check_recursion(&self) -> Vec<RecursionCycle> {
+        let exempt = |proc: ProcRef<'o>| {
+            matches!(
+                self.allow_recursion.get_self_or_parent(proc),
+                Some((_, true, _))
+            )
+        };
+        // Spawned calls start a new stack, so they can't recurse.
+        let mut graph = HashMap::<ProcRef<'o>, Vec<(ProcRef<'o>, Location)>>::new();
+        for (&caller, calls) in self.call_tree.iter() {
+            if exempt(caller) {
+                continue;
+            }
+            graph.insert(
+                caller,
+                calls
+                    .iter()
+                    .filter(|&&(callee, _, new_context)| !new_context && !exempt(callee))
+                    .map(|&(callee, location, _)| (callee, location))
+                    .collect(),
+            );
+        }
+        let calls_from = |proc: ProcRef<'o>| graph.get(&proc).map_or(&[][..], Vec::as_slice);
+
+        let mut procs: Vec<ProcRef<'o>> = graph.keys().copied().collect();
+        procs.sort_by_key(|proc| (proc.get().location, proc.to_string()));
+
+        // Tarjan's algorithm, iteratively to cope with long call chains.
+        let mut index = HashMap::<ProcRef<'o>, (usize, usize)>::new();
+        let mut on_stack = HashSet::<ProcRef<'o>>::new();
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        for &start in procs.iter() {
+            if index.contains_key(&start) {
+                continue;
+            }
+            let mut work = vec![(start, 0)];
+            while let Some(&(proc, next)) = work.last() {
+                if next == 0 && !index.contains_key(&proc) {
+                    let id = index.len();
+                    index.insert(proc, (id, id));
+                    stack.push(proc);
+                    on_stack.insert(proc);
+                }
+                if let Some(&(callee, _)) = calls_from(proc).get(next) {
+                    if let Some(top) = work.last_mut() {
+                        top.1 += 1;
+                    }
+                    match index.get(&callee) {
+                        None => work.push((callee, 0)),
+                        Some(&(callee_id, _)) if on_stack.contains(&callee) => {
+                            let entry = index.get_mut(&proc).unwrap();
+                            entry.1 = entry.1.min(callee_id);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                work.pop();
+                let (id, low) = index[&proc];
+                if let Some(&(caller, _)) = work.last() {
+                    let entry = index.get_mut(&caller).unwrap();
+                    entry.1 = entry.1.min(low);
+                }
+                if id == low {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(&member);
+                        component.push(member);
+                        if member == proc {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        let mut cycles = Vec::new();
+        for mut component in components {
+            component.sort_by_key(|proc| (proc.get().location, proc.to_string()));
+            let root = component[0];
+            let members: HashSet<ProcRef<'o>> = component.iter().copied().collect();
+
+            // The shortest way around the cycle, found breadth-first.
+            let mut came_from = HashMap::<ProcRef<'o>, (ProcRef<'o>, Location)>::new();
+            let mut to_visit = VecDeque::from([root]);
+            let mut closed_at = None;
+            'search: while let Some(proc) = to_visit.pop_front() {
+                for &(callee, location) in calls_from(proc) {
+                    if callee == root {
+                        closed_at = Some((proc, location));
+                        break 'search;
+                    }
+                    if members.contains(&callee) && !came_from.contains_key(&callee) {
+                        came_from.insert(callee, (proc, location));
+                        to_visit.push_back(callee);
+                    }
+                }
+            }
+            let Some((mut proc, location)) = closed_at else {
+                continue; // a lone proc which doesn't call itself
+            };
+            let mut path = vec![(proc, root, location)];
+            while proc != root {
+                let (caller, location) = came_from[&proc];
+                path.push((caller, proc, location));
+                proc = caller;
+            }
+            path.reverse();
+
+            let message = if path.len() == 1 {
+                format!("{} calls itself recursively", root)
+            } else {
+                let others: Vec<String> = path[1..]
+                    .iter()
+                    .map(|(caller, _, _)| caller.to_string())
+                    .collect();
+                format!("{} is mutually recursive with {}", root, others.join(", "))
+            };
+            let mut error = error(root.get().location, message)
+                .set_severity(Severity::Warning)
+                .with_errortype("recursion");
+            for &(_, callee, location) in path.iter() {
+                error.add_note(location, format!("{}() called here", callee));
+            }
+            error.register(self.context);
+
+            cycles.push(RecursionCycle {
+                calls: path
+                    .into_iter()
+                    .map(|(caller, callee, location)| {
+                        (caller.to_string(), callee.to_string(), location)
+                    })
+                    .collect(),
+            });
+        }
+        cycles
+    }
+
     /// Propagate violations make up the inheritence graph
     pub fn propagate_violations(&mut self, proc: ProcRef<'o>) {
         if proc.name() == "New" {
@@ -1090,6 +1498,276 @@ impl<'o> AnalyzeObjectTree<'o> {
     }
 }
 
+/// Builtins which return null when nothing is found.
+const NULLABLE_BUILTINS: &[&str] = &[
+    "get_step",
+    "get_step_away",
+    "get_step_rand",
+    "get_step_to",
+    "get_step_towards",
+];
+
+/// Builtins which are only true of non-null values.
+const TYPE_CHECK_BUILTINS: &[&str] = &[
+    "istype",
+    "isarea",
+    "isicon",
+    "islist",
+    "isloc",
+    "ismob",
+    "ismovable",
+    "isobj",
+    "isturf",
+];
+
+/// Mark locals as non-null, or as a more specific type, where `condition`
+/// being `truthy` implies it.
+fn narrow_locals<'o>(
+    objtree: &'o ObjectTree,
+    condition: &Expression,
+    truthy: bool,
+    local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
+) {
+    match condition {
+        Expression::Base { term, follow } => {
+            narrow_locals_term(objtree, &term.elem, follow, truthy, local_vars)
+        }
+        Expression::BinaryOp {
+            op: BinaryOp::And,
+            lhs,
+            rhs,
+        } if truthy => {
+            narrow_locals(objtree, lhs, true, local_vars);
+            narrow_locals(objtree, rhs, true, local_vars);
+        }
+        Expression::BinaryOp {
+            op: BinaryOp::Or,
+            lhs,
+            rhs,
+        } if !truthy => {
+            narrow_locals(objtree, lhs, false, local_vars);
+            narrow_locals(objtree, rhs, false, local_vars);
+        }
+        Expression::BinaryOp { op, lhs, rhs }
+            if (*op == BinaryOp::NotEq && truthy) || (*op == BinaryOp::Eq && !truthy) =>
+        {
+            match (lhs.as_term(), rhs.as_term()) {
+                (Some(Term::Ident(name)), Some(Term::Null))
+                | (Some(Term::Null), Some(Term::Ident(name))) => set_not_null(name, local_vars),
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+fn narrow_locals_term<'o>(
+    objtree: &'o ObjectTree,
+    term: &Term,
+    follow: &[Spanned<Follow>],
+    truthy: bool,
+    local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
+) {
+    if let Some((last, rest)) = follow.split_last() {
+        if let Follow::Unary(UnaryOp::Not) = last.elem {
+            narrow_locals_term(objtree, term, rest, !truthy, local_vars);
+        }
+        return;
+    }
+    match term {
+        Term::Expr(expr) => narrow_locals(objtree, expr, truthy, local_vars),
+        Term::Ident(name) if truthy => set_not_null(name, local_vars),
+        Term::Call(name, args) => {
+            let checks_type = truthy && TYPE_CHECK_BUILTINS.contains(&name.as_str());
+            let checks_null = !truthy && name.as_str() == "isnull";
+            if (checks_type || checks_null)
+                && let Some(Term::Ident(var)) = args.first().and_then(Expression::as_term)
+            {
+                set_not_null(var, local_vars);
+                if checks_type && let Some(ty) = checked_type(objtree, name, args) {
+                    set_narrower_type(var, ty, local_vars);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The type which a passing `istype()` or similar proves its first argument
+/// to have.
+fn checked_type<'o>(
+    objtree: &'o ObjectTree,
+    name: &str,
+    args: &[Expression],
+) -> Option<TypeRef<'o>> {
+    let path = match name {
+        "istype" => {
+            return match args.get(1)?.as_term()? {
+                Term::Prefab(prefab) if prefab.vars.is_empty() => {
+                    objtree.type_by_path(prefab.path.iter().map(|(_, piece)| piece))
+                }
+                _ => None,
+            };
+        }
+        "isarea" => "/area",
+        "isicon" => "/icon",
+        "islist" => "/list",
+        "isloc" => "/atom",
+        "ismob" => "/mob",
+        "ismovable" => "/atom/movable",
+        "isobj" => "/obj",
+        "isturf" => "/turf",
+        _ => return None,
+    };
+    objtree.find(path)
+}
+
+fn set_not_null(name: &str, local_vars: &mut HashMap<String, LocalVar, RandomState>) {
+    if let Some(var) = local_vars.get_mut(name) {
+        var.analysis.maybe_null = None;
+    }
+}
+
+fn set_narrower_type<'o>(
+    name: &str,
+    ty: TypeRef<'o>,
+    local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
+) {
+    if let Some(var) = local_vars.get_mut(name)
+        && !var
+            .analysis
+            .static_ty
+            .basic_type()
+            .is_some_and(|current| current.is_subtype_of(&ty))
+    {
+        var.analysis.static_ty = StaticType::Type(ty);
+    }
+}
+
+fn mark_assigned(name: &str, local_vars: &mut HashMap<String, LocalVar, RandomState>) {
+    if let Some(var) = local_vars.get_mut(name) {
+        var.assigned = true;
+    }
+}
+
+/// The value of an operand which is written out as a constant. Values carried
+/// along by local vars are not trusted, as they may have been reassigned.
+fn literal_operand(location: Location, expr: &Expression, analysis: &Analysis) -> Option<Constant> {
+    analysis.value.as_ref()?;
+    expr.to_owned().simple_evaluate(location).ok()
+}
+
+/// Forget what was known about the value of a local var which is written to.
+fn forget_value(name: &str, local_vars: &mut HashMap<String, LocalVar, RandomState>) {
+    if let Some(var) = local_vars.get_mut(name) {
+        var.analysis.value = None;
+        var.analysis.aset = AssumptionSet::default();
+    }
+}
+
+/// After a branch, a local may be null if it may be null along any of the
+/// ways out of the branch, and is assigned only if it is along all of them.
+/// Its type is the closest one covering every way out, if that is at least
+/// as specific as before, and otherwise the declared type. What is known of
+/// its value is kept only if every way out agrees on it.
+fn merge_branches<'o>(
+    local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
+    exits: &[HashMap<String, LocalVar<'o>, RandomState>],
+) {
+    if exits.is_empty() {
+        return;
+    }
+    for (name, var) in local_vars.iter_mut() {
+        var.analysis.maybe_null = exits
+            .iter()
+            .find_map(|each| each.get(name)?.analysis.maybe_null.clone());
+        var.assigned = exits
+            .iter()
+            .all(|each| each.get(name).is_some_and(|var| var.assigned));
+        if exits.iter().any(|each| {
+            each.get(name)
+                .is_none_or(|other| other.analysis.value != var.analysis.value)
+        }) {
+            var.analysis.value = None;
+            var.analysis.aset = AssumptionSet::default();
+        }
+
+        let merged = exits
+            .iter()
+            .map(|each| each.get(name).map(|var| &var.analysis.static_ty))
+            .try_fold(None, |merged: Option<StaticType<'o>>, ty| {
+                let ty = ty?;
+                match merged {
+                    None => Some(Some(ty.clone())),
+                    Some(merged) => common_type(merged, ty).map(Some),
+                }
+            })
+            .flatten();
+        var.analysis.static_ty = match merged {
+            Some(merged)
+                if match (merged.basic_type(), var.analysis.static_ty.basic_type()) {
+                    (Some(merged), Some(current)) => merged.is_subtype_of(&current),
+                    (_, None) => true,
+                    (None, Some(_)) => false,
+                } =>
+            {
+                merged
+            }
+            _ => var.declared.clone(),
+        };
+    }
+}
+
+/// The most specific type which both `a` and `b` are.
+fn common_type<'o>(a: StaticType<'o>, b: &StaticType<'o>) -> Option<StaticType<'o>> {
+    if a == *b {
+        return Some(a);
+    }
+    let (StaticType::Type(a), StaticType::Type(b)) = (a, b) else {
+        return None;
+    };
+    let mut ancestor = Some(a);
+    while let Some(ty) = ancestor {
+        if b.is_subtype_of(&ty) {
+            return Some(StaticType::Type(ty));
+        }
+        ancestor = ty.parent_type();
+    }
+    None
+}
+
+/// What a proc reference expression refers to.
+enum ProcReferenceTarget<'a> {
+    /// A path like `/datum/proc/foo` or `.proc/foo`.
+    Path(&'a [(PathOp, Ident)]),
+    /// A proc name in a string, like `"foo"`.
+    Name(&'a str),
+}
+
+/// Find the proc reference in an expression, looking through parentheses and
+/// `nameof()` as used by PROC_REF-style macros.
+fn proc_reference_target(expr: &Expression) -> Option<ProcReferenceTarget<'_>> {
+    match expr.as_term()? {
+        Term::Expr(inner) => proc_reference_target(inner),
+        Term::Call(name, args) if name.as_str() == "nameof" && args.len() == 1 => {
+            proc_reference_target(&args[0])
+        }
+        Term::Prefab(prefab)
+            if prefab.vars.is_empty()
+                && prefab
+                    .path
+                    .iter()
+                    .any(|(_, piece)| ProcDeclKind::from_name(piece).is_some()) =>
+        {
+            Some(ProcReferenceTarget::Path(&prefab.path))
+        }
+        Term::String(name) if !name.is_empty() && !name.contains('/') => {
+            Some(ProcReferenceTarget::Name(name))
+        }
+        _ => None,
+    }
+}
+
 fn static_type<'o>(
     objtree: &'o ObjectTree,
     location: Location,
@@ -1105,6 +1783,7 @@ fn static_type<'o>(
             "SpacemanDMM_final",
             "SpacemanDMM_private",
             "SpacemanDMM_protected",
+            "SpacemanDMM_deprecated",
         ]
         .contains(&&*of[0])
     {
@@ -1114,10 +1793,15 @@ fn static_type<'o>(
     if of.is_empty() {
         Ok(StaticType::None)
     } else if of[0] == "list" {
-        let keys = static_type(objtree, location, &of[1..])?;
+        // `list/mob/SpacemanDMM_list_of/obj` maps mobs to objs.
+        let (keys, values) = match of.iter().position(|each| each == "SpacemanDMM_list_of") {
+            Some(idx) => (&of[1..idx], &of[idx + 1..]),
+            None => (&of[1..], &[][..]),
+        };
         Ok(StaticType::List {
             list: objtree.expect("/list"),
-            keys: Box::new(keys),
+            keys: Box::new(static_type(objtree, location, keys)?),
+            values: Box::new(static_type(objtree, location, values)?),
         })
     } else if let Some(ty) = objtree.type_by_path(of) {
         Ok(StaticType::Type(ty))
@@ -1191,6 +1875,17 @@ pub fn check_var_defs(objtree: &ObjectTree, context: &Context) {
                     .register(context);
                 }
 
+                if decl.var_type.flags.is_deprecated() {
+                    DMError::new(
+                        typevar.value.location,
+                        format!("{} overrides deprecated var {:?}", path, varname),
+                    )
+                    .set_severity(Severity::Warning)
+                    .with_errortype("deprecated")
+                    .with_note(decl.location, format!("deprecated on {} here", parent.path))
+                    .register(context);
+                }
+
                 if decl.var_type.flags.is_private() {
                     DMError::new(
                         typevar.value.location,
@@ -1243,6 +1938,12 @@ impl ControlFlow {
         !self.fuzzy && (self.returns || self.breaks)
     }
 
+    /// Whether every path leaves the block, including by `break` or
+    /// `continue`, which `terminates` only counts for reachability.
+    pub fn leaves(&self) -> bool {
+        self.returns || self.continues || self.breaks
+    }
+
     pub fn no_else(&mut self) {
         self.returns = false;
         self.continues = false;
@@ -1300,31 +2001,69 @@ impl ControlFlow {
 struct LocalVar<'o> {
     location: Location,
     analysis: Analysis<'o>,
+    /// The type the var was declared with, which `istype()` may narrow.
+    declared: StaticType<'o>,
+    /// Whether an initializer or assignment is known to have run.
+    assigned: bool,
+    usage: Rc<LocalVarUsage>,
 }
 
 impl<'o> From<Analysis<'o>> for LocalVar<'o> {
     fn from(analysis: Analysis<'o>) -> Self {
         LocalVar {
             location: Location::default(),
+            declared: analysis.static_ty.clone(),
             analysis,
+            assigned: true,
+            usage: Default::default(),
         }
     }
 }
 
+/// Usage of a local var, shared by its copies in every nested scope.
+#[derive(Debug, Default)]
+struct LocalVarUsage {
+    read: Cell<bool>,
+    reported_unassigned: Cell<bool>,
+}
+
+/// How a local var came to be declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Declaration {
+    /// A `var/` statement.
+    Statement,
+    /// A loop or `catch` variable, assigned by the construct itself.
+    Loop,
+    /// The untyped value variable of a key-value `for` loop.
+    Implicit,
+}
+
 struct AnalyzeProc<'o, 's> {
-    env: &'s mut AnalyzeObjectTree<'o>,
-    context: &'o Context,
+    env: ProcEnv<'o, 's>,
+    context: &'s Context,
     objtree: &'o ObjectTree,
     ty: TypeRef<'o>,
     proc_ref: ProcRef<'o>,
     calls_parent: bool,
+    /// Whether `..()` is called without arguments, passing ours along.
+    forwards_args: bool,
     inside_newcontext: u32,
+    /// Local vars declared by `var/` statements and loops, in order.
+    declared_locals: Vec<(String, Location, Rc<LocalVarUsage>)>,
+    /// The operator assigning to the var about to be visited, if any.
+    assign_target: Option<AssignOp>,
+    /// Whether each enclosing loop has a way out, innermost last.
+    loop_exits: Vec<bool>,
+    /// Calls whose target can't be known, such as `x:foo()`.
+    opaque_calls: u32,
+    /// The typed list whose `Add()` is about to be visited, if any.
+    list_add: Option<StaticType<'o>>,
 }
 
 impl<'o, 's> AnalyzeProc<'o, 's> {
     fn new(
-        env: &'s mut AnalyzeObjectTree<'o>,
-        context: &'o Context,
+        env: ProcEnv<'o, 's>,
+        context: &'s Context,
         objtree: &'o ObjectTree,
         proc_ref: ProcRef<'o>,
     ) -> Self {
@@ -1337,7 +2076,13 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             ty,
             proc_ref,
             calls_parent: false,
+            forwards_args: false,
             inside_newcontext: 0,
+            declared_locals: Vec::new(),
+            assign_target: None,
+            loop_exits: Vec::new(),
+            opaque_calls: 0,
+            list_add: None,
         }
     }
 
@@ -1372,6 +2117,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 value: None,
                 fix_hint: None,
                 is_impure: Some(true),
+                maybe_null: None,
             }
             .into(),
         );
@@ -1383,18 +2129,31 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 param.name.to_owned(),
                 LocalVar {
                     location: self.proc_ref.location,
+                    declared: analysis.static_ty.clone(),
                     analysis,
+                    assigned: true,
+                    usage: Default::default(),
                 },
             );
             //println!("adding parameters {:#?}", self.local_vars);
         }
+        let param_usage: Vec<_> = self
+            .proc_ref
+            .get()
+            .parameters
+            .iter()
+            .map(|param| local_vars[param.name.as_str()].usage.clone())
+            .collect();
 
         self.visit_block(block, &mut local_vars);
 
+        self.check_unused_locals(&param_usage, local_vars["args"].usage.read.get());
+
         //println!("purity {}", self.is_pure);
 
         if let Some(parent) = self.proc_ref.parent_proc() {
-            if let Some((proc, true, location)) = self.env.private.get_self_or_parent(self.proc_ref)
+            if let Some((proc, true, location)) =
+                self.env.settings.private.get_self_or_parent(self.proc_ref)
                 && proc != self.proc_ref
             {
                 error(
@@ -1405,8 +2164,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 .with_errortype("private_proc")
                 .register(self.context);
             }
-            if let Some((proc, true, location)) =
-                self.env.must_not_override.get_self_or_parent(self.proc_ref)
+            if let Some((proc, true, location)) = self
+                .env
+                .settings
+                .must_not_override
+                .get_self_or_parent(self.proc_ref)
                 && proc != self.proc_ref
             {
                 error(
@@ -1418,8 +2180,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 .register(self.context);
             }
             if !self.calls_parent
-                && let Some((proc, true, location)) =
-                    self.env.must_call_parent.get_self_or_parent(self.proc_ref)
+                && let Some((proc, true, location)) = self
+                    .env
+                    .settings
+                    .must_call_parent
+                    .get_self_or_parent(self.proc_ref)
             {
                 error(
                     self.proc_ref.location,
@@ -1429,10 +2194,32 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 .with_errortype("must_call_parent")
                 .register(self.context);
             }
+            if !self.calls_parent {
+                self.check_parent_call_lints();
+            }
+            if self.env.settings.deprecated.get(self.proc_ref).is_none()
+                && let Some((deprecated, true, location)) =
+                    self.env.settings.deprecated.get_self_or_parent(parent)
+            {
+                error(
+                    self.proc_ref.location,
+                    format!(
+                        "{} overrides deprecated proc {}{}",
+                        self.proc_ref,
+                        deprecated,
+                        self.deprecation_suffix(deprecated)
+                    ),
+                )
+                .set_severity(Severity::Warning)
+                .with_errortype("deprecated")
+                .with_note(location, "deprecated here")
+                .register(self.context);
+            }
             if !parent.is_builtin()
                 && self.proc_ref.ty() == parent.ty()
                 && self
                     .env
+                    .settings
                     .can_be_redefined
                     .get_self_or_parent(self.proc_ref)
                     .is_none()
@@ -1468,6 +2255,74 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         term
     }
 
+    /// Visit the body of a loop, remembering it for the `loop_never_yields`
+    /// check if its condition is always true and nothing leaves it.
+    fn visit_loop_block(
+        &mut self,
+        location: Location,
+        always_true: bool,
+        block: &'o [Spanned<Statement>],
+        local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
+    ) -> ControlFlow {
+        let proc_ref = self.proc_ref;
+        let calls_before = self
+            .env
+            .findings
+            .call_tree
+            .get(&proc_ref)
+            .map_or(0, Vec::len);
+        let sleeps_before = self
+            .env
+            .findings
+            .sleeping_procs
+            .get_violators(proc_ref)
+            .map_or(0, Vec::len);
+        let opaque_before = self.opaque_calls;
+
+        self.loop_exits.push(false);
+        let state = self.visit_block(block, local_vars);
+        let exits = self.loop_exits.pop().unwrap_or(false);
+
+        let sleeps_after = self
+            .env
+            .findings
+            .sleeping_procs
+            .get_violators(proc_ref)
+            .map_or(0, Vec::len);
+        if always_true
+            && !exits
+            && sleeps_after == sleeps_before
+            && self.opaque_calls == opaque_before
+        {
+            let calls = self
+                .env
+                .findings
+                .call_tree
+                .get(&proc_ref)
+                .map(|calls| {
+                    calls[calls_before..]
+                        .iter()
+                        .map(|&(proc, _, new_context)| (proc, new_context))
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.env
+                .findings
+                .endless_loops
+                .push(EndlessLoop { location, calls });
+        }
+        state
+    }
+
+    /// Note that control leaves the innermost loop, or every loop.
+    fn exit_loops(&mut self, all: bool) {
+        if all {
+            self.loop_exits.iter_mut().for_each(|exits| *exits = true);
+        } else if let Some(exits) = self.loop_exits.last_mut() {
+            *exits = true;
+        }
+    }
+
     fn loop_condition_check(&mut self, location: Location, expression: &'o Expression) {
         match expression.is_truthy() {
             Some(true) => {
@@ -1512,7 +2367,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                             && !follow.iter().any(|f| matches!(f.elem, Follow::Call(..)))
                             && let Some(proc) = self.ty.get_proc(call)
                             && let Some((_, _, loc)) =
-                                self.env.must_be_pure.get_self_or_parent(proc)
+                                self.env.settings.must_be_pure.get_self_or_parent(proc)
                         {
                             error(
                                 location,
@@ -1531,7 +2386,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         if let Some(impurity) = lhsanalysis.is_impure
                             && impurity
                         {
-                            self.env.impure_procs.insert_violator(
+                            self.env.findings.impure_procs.insert_violator(
                                 self.proc_ref,
                                 "purity breaking << on expression",
                                 location,
@@ -1550,7 +2405,25 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         .register(self.context);
                 }
                 let return_type = self.visit_expression(location, expr, None, local_vars);
+                if let Some(TypeExpr::Static(declared)) =
+                    self.env.settings.return_type.get(&self.proc_ref)
+                {
+                    let proc_ref = self.proc_ref;
+                    self.check_type_mismatch(
+                        location,
+                        declared.basic_type(),
+                        &return_type,
+                        "type_mismatch_return",
+                        |declared, actual| {
+                            format!(
+                                "type mismatch: {} returns {}, but its return type is {}",
+                                proc_ref, actual, declared
+                            )
+                        },
+                    );
+                }
                 local_vars.get_mut(".").unwrap().analysis = return_type;
+                self.exit_loops(true);
                 return ControlFlow {
                     returns: true,
                     continues: false,
@@ -1559,6 +2432,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 };
             }
             Statement::Return(None) => {
+                self.exit_loops(true);
                 return ControlFlow {
                     returns: true,
                     continues: false,
@@ -1570,6 +2444,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 if let Some(expr) = expr {
                     self.visit_expression(location, expr, None, local_vars);
                 }
+                self.exit_loops(true);
                 return ControlFlow {
                     returns: true,
                     continues: false,
@@ -1579,18 +2454,23 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
             Statement::Throw(expr) => {
                 self.visit_expression(location, expr, None, local_vars);
+                self.exit_loops(true);
             }
             Statement::While { condition, block } => {
                 let mut scoped_locals = local_vars.clone();
                 // We don't check for static/determine conditions because while(TRUE) is so common.
                 self.visit_expression(location, condition, None, &mut scoped_locals);
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let always_true = condition.is_truthy() == Some(true);
+                let mut state =
+                    self.visit_loop_block(location, always_true, block, &mut scoped_locals);
                 state.end_loop();
                 return state;
             }
             Statement::DoWhile { block, condition } => {
                 let mut scoped_locals = local_vars.clone();
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let always_true = condition.elem.is_truthy() == Some(true);
+                let mut state =
+                    self.visit_loop_block(location, always_true, block, &mut scoped_locals);
                 if state.terminates_loop() {
                     error(
                         location,
@@ -1605,6 +2485,12 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     None,
                     &mut scoped_locals,
                 );
+                // The body always runs once, so what it assigns stays assigned.
+                for (name, var) in scoped_locals.iter() {
+                    if var.assigned {
+                        mark_assigned(name, local_vars);
+                    }
+                }
 
                 state.end_loop();
                 return state;
@@ -1612,8 +2498,12 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             Statement::If { arms, else_arm } => {
                 let mut allterm = ControlFlow::alltrue();
                 let mut alwaystrue = false;
+                // The locals as of each way out of the if, for null and
+                // assignment checking.
+                let mut fallthrough_locals = local_vars.clone();
+                let mut exits = Vec::new();
                 for (condition, block) in arms.iter() {
-                    let mut scoped_locals = local_vars.clone();
+                    let mut scoped_locals = fallthrough_locals.clone();
                     self.visit_control_condition(condition.location, &condition.elem);
                     if alwaystrue {
                         error(condition.location,"unreachable if block, preceeding if/elseif condition(s) are always true")
@@ -1626,7 +2516,17 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         None,
                         &mut scoped_locals,
                     );
+                    narrow_locals(self.objtree, &condition.elem, true, &mut scoped_locals);
+                    narrow_locals(
+                        self.objtree,
+                        &condition.elem,
+                        false,
+                        &mut fallthrough_locals,
+                    );
                     let state = self.visit_block(block, &mut scoped_locals);
+                    if !state.leaves() {
+                        exits.push(scoped_locals);
+                    }
                     match condition.elem.is_truthy() {
                         Some(true) => {
                             error(condition.location, "if condition is always true")
@@ -1649,9 +2549,16 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                                 .with_errortype("unreachable_code")
                                 .register(self.context);
                     }
-                    let state = self.visit_block(else_arm, &mut local_vars.clone());
+                    let mut else_locals = fallthrough_locals;
+                    let state = self.visit_block(else_arm, &mut else_locals);
+                    if !state.leaves() {
+                        exits.push(else_locals);
+                    }
+                    merge_branches(local_vars, &exits);
                     allterm.merge_false(state);
                 } else {
+                    exits.push(fallthrough_locals);
+                    merge_branches(local_vars, &exits);
                     allterm.no_else();
                     return allterm;
                 }
@@ -1660,7 +2567,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
             Statement::ForInfinite { block } => {
                 let mut scoped_locals = local_vars.clone();
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let mut state = self.visit_loop_block(location, true, block, &mut scoped_locals);
                 state.end_loop();
                 return state;
             }
@@ -1682,7 +2589,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 if let Some(inc) = inc {
                     self.visit_statement(location, inc, &mut scoped_locals);
                 }
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let always_true = test
+                    .as_ref()
+                    .is_none_or(|test| test.is_truthy() == Some(true));
+                let mut state =
+                    self.visit_loop_block(location, always_true, block, &mut scoped_locals);
                 state.end_loop();
                 return state;
             }
@@ -1695,6 +2606,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     block,
                 } = &**for_list;
                 let mut scoped_locals = local_vars.clone();
+                let mut elements = StaticType::None;
                 if let Some(in_list) = in_list {
                     let list = self.visit_expression(location, in_list, None, &mut scoped_locals);
                     match list.static_ty {
@@ -1702,7 +2614,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                             // Occurs extremely often due to DM not complaining about this, with
                             // over 800 detections on /tg/. Maybe a future lint.
                         }
-                        StaticType::List { .. } => { /* OK */ }
+                        StaticType::List { keys, .. } => elements = *keys,
                         StaticType::Type(ty) => {
                             if ty != self.objtree.expect("/world")
                                 && ty != self.objtree.expect("/list")
@@ -1736,9 +2648,25 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     }
                 }
                 if let Some(var_type) = var_type {
-                    self.visit_var(location, var_type, name, None, &mut scoped_locals);
+                    self.visit_var(
+                        location,
+                        var_type,
+                        name,
+                        None,
+                        Declaration::Loop,
+                        &mut scoped_locals,
+                    );
+                    // An untyped loop var takes the type of the list's elements.
+                    if var_type.type_path.is_empty()
+                        && let Some(local) = scoped_locals.get_mut(name.as_str())
+                    {
+                        local.declared = elements.clone();
+                        local.analysis.static_ty = elements;
+                    }
+                } else {
+                    mark_assigned(name, &mut scoped_locals);
                 }
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let mut state = self.visit_loop_block(location, false, block, &mut scoped_locals);
                 state.end_loop();
                 return state;
             }
@@ -1757,9 +2685,19 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     self.visit_expression(location, step, None, &mut scoped_locals);
                 }
                 if let Some(var_type) = var_type {
-                    self.visit_var(location, var_type, name, Some(start), &mut scoped_locals);
+                    self.visit_var(
+                        location,
+                        var_type,
+                        name,
+                        Some(start),
+                        Declaration::Loop,
+                        &mut scoped_locals,
+                    );
+                } else {
+                    self.visit_expression(location, start, None, &mut scoped_locals);
+                    mark_assigned(name, &mut scoped_locals);
                 }
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                let mut state = self.visit_loop_block(location, false, block, &mut scoped_locals);
                 if let Some(startterm) = start.as_term()
                     && let Some(endterm) = end.as_term()
                     && let Some(validity) = startterm.valid_for_range(endterm, step.as_ref())
@@ -1796,7 +2734,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     Some(Term::Ident(i)) if i == "FALSE" => true,
                     _ => false,
                 } {
-                    self.env.waitfor_procs.insert(self.proc_ref);
+                    self.env.findings.waitfor_procs.insert(self.proc_ref);
                 }
             }
             Statement::Setting { .. } => {}
@@ -1818,6 +2756,15 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 let mut allterm = ControlFlow::alltrue();
                 self.visit_control_condition(location, input);
                 self.visit_expression(location, input, None, local_vars);
+                check_switch_cases(
+                    input,
+                    cases,
+                    default,
+                    location,
+                    self.env.settings.literal_macros,
+                    self.context,
+                );
+                let mut exits = Vec::new();
                 for (case, block) in cases.iter() {
                     let mut scoped_locals = local_vars.clone();
                     if let [
@@ -1852,12 +2799,22 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         }
                     }
                     let state = self.visit_block(block, &mut scoped_locals);
+                    if !state.leaves() {
+                        exits.push(scoped_locals);
+                    }
                     allterm.merge_false(state);
                 }
                 if let Some(default) = default {
-                    let state = self.visit_block(default, &mut local_vars.clone());
+                    let mut default_locals = local_vars.clone();
+                    let state = self.visit_block(default, &mut default_locals);
+                    if !state.leaves() {
+                        exits.push(default_locals);
+                    }
+                    merge_branches(local_vars, &exits);
                     allterm.merge_false(state);
                 } else {
+                    exits.push(local_vars.clone());
+                    merge_branches(local_vars, &exits);
                     allterm.no_else();
                     return allterm;
                 }
@@ -1869,7 +2826,12 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 catch_params,
                 catch_block,
             } => {
-                self.visit_block(try_block, &mut local_vars.clone());
+                let mut exits = Vec::new();
+                let mut try_locals = local_vars.clone();
+                let state = self.visit_block(try_block, &mut try_locals);
+                if !state.leaves() {
+                    exits.push(try_locals);
+                }
                 if catch_params.len() > 1 {
                     error(
                         location,
@@ -1892,9 +2854,20 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         _ => {}
                     }
                     let var_type: VarType = type_path.iter().map(ToOwned::to_owned).collect();
-                    self.visit_var(location, &var_type, var_name, None, &mut catch_locals);
+                    self.visit_var(
+                        location,
+                        &var_type,
+                        var_name,
+                        None,
+                        Declaration::Loop,
+                        &mut catch_locals,
+                    );
+                }
+                let state = self.visit_block(catch_block, &mut catch_locals);
+                if !state.leaves() {
+                    exits.push(catch_locals);
                 }
-                self.visit_block(catch_block, &mut catch_locals);
+                merge_branches(local_vars, &exits);
             }
             Statement::Continue(_) => {
                 return ControlFlow {
@@ -1904,7 +2877,9 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     fuzzy: true,
                 };
             }
-            Statement::Break(_) => {
+            Statement::Break(label) => {
+                // A labelled break may leave any of the enclosing loops.
+                self.exit_loops(label.is_some());
                 return ControlFlow {
                     returns: false,
                     continues: false,
@@ -1912,12 +2887,17 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     fuzzy: true,
                 };
             }
-            Statement::Goto(_) => {}
+            Statement::Goto(_) => self.exit_loops(true),
             Statement::Label { name: _, block } => {
                 self.visit_block(block, &mut local_vars.clone());
             }
             Statement::Del(expr) => {
+                self.check_call_lints(location, "del", |path| path.is_empty());
                 self.visit_expression(location, expr, None, local_vars);
+                // Deleting src ends the proc.
+                if matches!(expr.as_term(), Some(Term::Ident(name)) if name == "src") {
+                    self.exit_loops(true);
+                }
             }
             Statement::ForKeyValue(for_key_value) => {
                 let ForKeyValueStatement {
@@ -1929,6 +2909,8 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 } = &**for_key_value;
 
                 let mut scoped_locals = local_vars.clone();
+                let mut keys = StaticType::None;
+                let mut values = StaticType::None;
 
                 if let Some(in_list) = in_list {
                     let list = self.visit_expression(location, in_list, None, &mut scoped_locals);
@@ -1939,7 +2921,14 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                             // over 800 detections on /tg/. Maybe a future lint.
                         }
 
-                        StaticType::List { .. } => { /* OK */ }
+                        StaticType::List {
+                            keys: list_keys,
+                            values: list_values,
+                            ..
+                        } => {
+                            keys = *list_keys;
+                            values = *list_values;
+                        }
 
                         StaticType::Type(ty) => {
                             if ty != self.objtree.expect("/world")
@@ -1980,16 +2969,42 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 // This quite ugly but DM doesn't let you do for (var/k, var/v)
                 // only the type of the key is taken into account
                 if let Some(var_type) = var_type {
-                    self.visit_var(location, var_type, key, None, &mut scoped_locals);
+                    self.visit_var(
+                        location,
+                        var_type,
+                        key,
+                        None,
+                        Declaration::Loop,
+                        &mut scoped_locals,
+                    );
+                    if var_type.type_path.is_empty()
+                        && let Some(local) = scoped_locals.get_mut(key.as_str())
+                    {
+                        local.declared = keys.clone();
+                        local.analysis.static_ty = keys;
+                    }
+                } else {
+                    mark_assigned(key, &mut scoped_locals);
                 }
-                // the "v" in a DM for (var/k, v) statement is essentially typeless.
-                // There is currently no way to change that.
+                // the "v" in a DM for (var/k, v) statement can't be declared
+                // with a type, so it takes the list's associated values.
                 let var_type_value = VarType {
                     flags: VarTypeFlags::default(),
                     type_path: Box::new([]),
                 };
-                self.visit_var(location, &var_type_value, value, None, &mut scoped_locals);
-                let mut state = self.visit_block(block, &mut scoped_locals);
+                self.visit_var(
+                    location,
+                    &var_type_value,
+                    value,
+                    None,
+                    Declaration::Implicit,
+                    &mut scoped_locals,
+                );
+                if let Some(local) = scoped_locals.get_mut(value.as_str()) {
+                    local.declared = values.clone();
+                    local.analysis.static_ty = values;
+                }
+                let mut state = self.visit_loop_block(location, false, block, &mut scoped_locals);
                 state.end_loop();
                 return state;
             }
@@ -1997,6 +3012,72 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         ControlFlow::allfalse()
     }
 
+    fn check_shadowing(
+        &mut self,
+        location: Location,
+        name: &str,
+        local_vars: &HashMap<String, LocalVar<'o>, RandomState>,
+    ) {
+        if let Some(outer) = local_vars.get(name)
+            && outer.location != Location::default()
+        {
+            error(
+                location,
+                format!("local var {:?} shadows an earlier local var", name),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("shadowed_var")
+            .with_note(outer.location, "shadowed var declared here")
+            .register(self.context);
+        } else if !self.ty.is_root()
+            && let Some(decl) = self.ty.get_var_declaration(name)
+        {
+            error(
+                location,
+                format!("local var {:?} shadows var on {}", name, self.ty),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("shadowed_var")
+            .with_note(decl.location, "shadowed var declared here")
+            .register(self.context);
+        }
+    }
+
+    fn check_unused_locals(&mut self, param_usage: &[Rc<LocalVarUsage>], args_read: bool) {
+        for (name, location, usage) in std::mem::take(&mut self.declared_locals) {
+            if !usage.read.get() && !name.starts_with('_') {
+                error(location, format!("unused local var: {:?}", name))
+                    .set_severity(Severity::Warning)
+                    .with_errortype("unused_local_var")
+                    .register(self.context);
+            }
+        }
+
+        // Overrides must accept the arguments they are given, so only
+        // parameters of procs which are neither overrides nor overridden
+        // are reported.
+        let proc = self.proc_ref;
+        if args_read || self.forwards_args || proc.parent_proc().is_some() {
+            return;
+        }
+        let mut overridden = !proc.is_externally_visible();
+        proc.recurse_children(&mut |child| overridden |= child.ty() != proc.ty());
+        if overridden {
+            return;
+        }
+        for (param, usage) in proc.get().parameters.iter().zip(param_usage) {
+            if !usage.read.get() && !param.name.starts_with('_') {
+                error(
+                    param.location,
+                    format!("unused parameter {:?} of {}", param.name, proc),
+                )
+                .set_severity(Severity::Warning)
+                .with_errortype("unused_parameter")
+                .register(self.context);
+            }
+        }
+    }
+
     fn visit_var_stmt(
         &mut self,
         location: Location,
@@ -2008,6 +3089,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             &var.var_type,
             &var.name,
             var.value.as_ref(),
+            Declaration::Statement,
             local_vars,
         )
     }
@@ -2018,21 +3100,58 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         var_type: &VarType,
         name: &str,
         value: Option<&'o Expression>,
+        declaration: Declaration,
         local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
     ) {
         // Calculate type hint
-        let static_type = self.env.static_type(location, &var_type.type_path);
+        let static_type = self.static_type(location, &var_type.type_path).static_ty;
         // Visit the expression if it's there
         let mut analysis = match value {
             Some(expr) => {
-                self.visit_expression(location, expr, static_type.basic_type(), local_vars)
+                let analysis =
+                    self.visit_expression(location, expr, static_type.basic_type(), local_vars);
+                self.check_type_mismatch(
+                    location,
+                    static_type.basic_type(),
+                    &analysis,
+                    "type_mismatch_assignment",
+                    |declared, actual| {
+                        format!(
+                            "type mismatch: assigning {} to {}, which is a {}",
+                            actual, name, declared
+                        )
+                    },
+                );
+                analysis
             }
             None => Analysis::null(),
         };
         analysis.static_ty = static_type;
 
+        if declaration != Declaration::Implicit {
+            self.check_shadowing(location, name, local_vars);
+        }
+
         // Save var to locals
-        local_vars.insert(name.to_owned(), LocalVar { location, analysis });
+        let usage = Rc::new(LocalVarUsage::default());
+        if declaration != Declaration::Implicit {
+            self.declared_locals
+                .push((name.to_owned(), location, usage.clone()));
+        }
+        let assigned = value.is_some()
+            || declaration != Declaration::Statement
+            || var_type.flags.is_static()
+            || var_type.flags.is_const();
+        local_vars.insert(
+            name.to_owned(),
+            LocalVar {
+                location,
+                declared: analysis.static_ty.clone(),
+                analysis,
+                assigned,
+                usage,
+            },
+        );
     }
 
     fn visit_expression(
@@ -2045,9 +3164,56 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         match expression {
             Expression::Base { term, follow } => {
                 let base_type_hint = if follow.is_empty() { type_hint } else { None };
+                // Only the last part of the expression is being assigned to.
+                // `++` and `--` both read and write what they apply to.
+                let steps = follow.iter().any(|each| {
+                    matches!(
+                        each.elem,
+                        Follow::Unary(
+                            UnaryOp::PreIncr
+                                | UnaryOp::PostIncr
+                                | UnaryOp::PreDecr
+                                | UnaryOp::PostDecr
+                        )
+                    )
+                });
+                let assign_target = self
+                    .assign_target
+                    .take()
+                    .or(steps.then_some(AssignOp::AddAssign));
+                let target_len = follow
+                    .iter()
+                    .rposition(|each| !matches!(each.elem, Follow::Unary(_)))
+                    .map_or(0, |i| i + 1);
+                if target_len == 0 && matches!(term.elem, Term::Ident(_)) {
+                    self.assign_target = assign_target;
+                }
                 let mut ty = self.visit_term(term.location, &term.elem, base_type_hint, local_vars);
-                for each in follow.iter() {
+                self.assign_target = None;
+                for (i, each) in follow.iter().enumerate() {
+                    if i + 1 == target_len && matches!(each.elem, Follow::Field(..)) {
+                        self.assign_target = assign_target;
+                    }
                     ty = self.visit_follow(each.location, ty, &each.elem, local_vars);
+                    self.assign_target = None;
+                }
+                if steps && let Term::Ident(name) = &term.elem {
+                    forget_value(name, local_vars);
+                }
+                if let Some(first) = follow.first()
+                    && let Follow::Unary(op @ UnaryOp::BitNot) = first.elem
+                {
+                    let value = match term.elem {
+                        Term::Int(number) => Some(Constant::from(number)),
+                        Term::Float(number) => Some(Constant::from(number)),
+                        _ => None,
+                    };
+                    if let Some(value) = value {
+                        self.check_constant_op(
+                            first.location,
+                            dm::constants::check_unary_op(op, &value),
+                        );
+                    }
                 }
                 ty
             }
@@ -2059,19 +3225,19 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 let lty = self.visit_expression(location, lhs, None, local_vars);
 
                 if lty.static_ty == StaticType::Type(self.objtree.expect("/mob")) {
-                    self.env.impure_procs.insert_violator(
+                    self.env.findings.impure_procs.insert_violator(
                         self.proc_ref,
                         "LShift onto mob",
                         location,
                     );
                 } else if lty.static_ty == StaticType::Type(self.objtree.expect("/savefile")) {
-                    self.env.impure_procs.insert_violator(
+                    self.env.findings.impure_procs.insert_violator(
                         self.proc_ref,
                         "LShift onto savefile",
                         location,
                     );
                 } else if lty.static_ty == StaticType::Type(self.objtree.expect("/list")) {
-                    self.env.impure_procs.insert_violator(
+                    self.env.findings.impure_procs.insert_violator(
                         self.proc_ref,
                         "LShift onto list",
                         location,
@@ -2079,6 +3245,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 }
 
                 let rty = self.visit_expression(location, rhs, None, local_vars);
+                self.check_constant_binary(location, BinaryOp::LShift, (lhs, &lty), (rhs, &rty));
                 self.visit_binary(lty, rty, BinaryOp::LShift)
             }
             Expression::BinaryOp {
@@ -2178,9 +3345,22 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 // this is the only case I've seen it used in the wild.
                 // ex: var/datum/cache_entry/E = cache[key] || new
                 let lty = self.visit_expression(location, lhs, type_hint, local_vars);
-                let rty = self.visit_expression(location, rhs, type_hint, local_vars);
+                let mut rhs_locals = local_vars.clone();
+                narrow_locals(self.objtree, lhs, false, &mut rhs_locals);
+                let rty = self.visit_expression(location, rhs, type_hint, &mut rhs_locals);
                 self.visit_binary(lty, rty, BinaryOp::Or)
             }
+            Expression::BinaryOp {
+                op: BinaryOp::And,
+                lhs,
+                rhs,
+            } => {
+                let lty = self.visit_expression(location, lhs, None, local_vars);
+                let mut rhs_locals = local_vars.clone();
+                narrow_locals(self.objtree, lhs, true, &mut rhs_locals);
+                let rty = self.visit_expression(location, rhs, None, &mut rhs_locals);
+                self.visit_binary(lty, rty, BinaryOp::And)
+            }
             Expression::BinaryOp { op, lhs, rhs } => {
                 let lty = self.visit_expression(location, lhs, None, local_vars);
                 let rty = self.visit_expression(location, rhs, None, local_vars);
@@ -2196,24 +3376,104 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     }
                     _ => {}
                 }
+                self.check_constant_binary(location, *op, (lhs, &lty), (rhs, &rty));
                 self.visit_binary(lty, rty, *op)
             }
-            Expression::AssignOp { lhs, rhs, .. } => {
-                let lhs = self.visit_expression(location, lhs, None, local_vars);
-                if let Some(true) = lhs.is_impure {
-                    self.env.impure_procs.insert_violator(
+            Expression::AssignOp { op, lhs, rhs } => {
+                // Plain assignment to a local doesn't read it.
+                let assigned_local = match lhs.as_term() {
+                    Some(Term::Ident(name)) if *op == AssignOp::Assign => {
+                        local_vars.get(name).map(|var| {
+                            let mut analysis = var.analysis.clone();
+                            analysis.static_ty = var.declared.clone();
+                            (name, var.location, analysis)
+                        })
+                    }
+                    _ => None,
+                };
+                let lty = match &assigned_local {
+                    Some((name, var_location, analysis)) => {
+                        if *var_location == Location::default()
+                            && let Expression::Base { term, .. } = &**lhs
+                        {
+                            self.assign_target = Some(*op);
+                            self.check_var_lints(term.location, None, name);
+                            self.assign_target = None;
+                        }
+                        analysis
+                            .clone()
+                            .with_fix_hint(*var_location, "add additional type info here")
+                    }
+                    None => {
+                        self.assign_target = Some(*op);
+                        let lty = self.visit_expression(location, lhs, None, local_vars);
+                        self.assign_target = None;
+                        lty
+                    }
+                };
+                if let Some(true) = lty.is_impure {
+                    self.env.findings.impure_procs.insert_violator(
                         self.proc_ref,
                         "Assignment on purity breaking expression",
                         location,
                     );
                 }
-                self.visit_expression(location, rhs, lhs.static_ty.basic_type(), local_vars)
+                let rty =
+                    self.visit_expression(location, rhs, lty.static_ty.basic_type(), local_vars);
+                if matches!(op, AssignOp::AddAssign | AssignOp::BitOrAssign) {
+                    self.check_list_add(location, &lty.static_ty, &rty);
+                }
+                if *op == AssignOp::Assign
+                    && let Expression::Base { term, follow } = &**lhs
+                {
+                    let name = match follow.last().map(|each| &each.elem) {
+                        None => match &term.elem {
+                            Term::Ident(name) => Some(name.as_str()),
+                            Term::GlobalIdent(name) => Some(name.as_str()),
+                            _ => None,
+                        },
+                        Some(Follow::Field(_, name)) => Some(name.as_str()),
+                        Some(_) => None,
+                    };
+                    if let Some(name) = name {
+                        self.check_type_mismatch(
+                            location,
+                            lty.static_ty.basic_type(),
+                            &rty,
+                            "type_mismatch_assignment",
+                            |declared, actual| {
+                                format!(
+                                    "type mismatch: assigning {} to {}, which is a {}",
+                                    actual, name, declared
+                                )
+                            },
+                        );
+                    }
+                }
+                if *op == AssignOp::Assign
+                    && let Some(Term::Ident(name)) = lhs.as_term()
+                    && let Some(var) = local_vars.get_mut(name)
+                {
+                    var.analysis.maybe_null = rty.maybe_null.clone();
+                    var.analysis.static_ty = var.declared.clone();
+                }
+                if let Some(Term::Ident(name)) = lhs.as_term() {
+                    forget_value(name, local_vars);
+                }
+                if let Some((name, _, _)) = assigned_local {
+                    mark_assigned(name, local_vars);
+                }
+                rty
             }
             Expression::TernaryOp { cond, if_, else_ } => {
                 // TODO: be sensible
                 self.visit_expression(location, cond, None, local_vars);
-                let ty = self.visit_expression(location, if_, type_hint, local_vars);
-                self.visit_expression(location, else_, type_hint, local_vars);
+                let mut if_locals = local_vars.clone();
+                narrow_locals(self.objtree, cond, true, &mut if_locals);
+                let ty = self.visit_expression(location, if_, type_hint, &mut if_locals);
+                let mut else_locals = local_vars.clone();
+                narrow_locals(self.objtree, cond, false, &mut else_locals);
+                self.visit_expression(location, else_, type_hint, &mut else_locals);
                 ty
             }
         }
@@ -2248,6 +3508,23 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
 
             Term::Ident(unscoped_name) => {
                 if let Some(var) = local_vars.get(unscoped_name) {
+                    if var.location == Location::default() {
+                        self.check_var_lints(location, None, unscoped_name);
+                    }
+                    var.usage.read.set(true);
+                    if !var.assigned && !var.usage.reported_unassigned.replace(true) {
+                        error(
+                            location,
+                            format!(
+                                "local var {:?} is read before it is assigned",
+                                unscoped_name
+                            ),
+                        )
+                        .set_severity(Severity::Warning)
+                        .with_errortype("use_before_assign")
+                        .with_note(var.location, "declared here")
+                        .register(self.context);
+                    }
                     var.analysis
                         .clone()
                         .with_fix_hint(var.location, "add additional type info here")
@@ -2269,8 +3546,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         value: Some(Constant::Prefab(Box::new(pop))),
                         fix_hint: None,
                         is_impure: None,
+                        maybe_null: None,
                     }
                 } else if let Some(decl) = self.ty.get_var_declaration(unscoped_name) {
+                    self.check_var_lints(location, Some(self.ty), unscoped_name);
+                    self.check_deprecated_var(location, self.ty, unscoped_name, decl);
                     let mut ana = self
                         .static_type(location, &decl.var_type.type_path)
                         .with_fix_hint(decl.location, "add additional type info here");
@@ -2314,7 +3594,12 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         value: Some(Constant::Prefab(Box::new(pop))),
                         fix_hint: None,
                         is_impure: None,
+                        maybe_null: None,
                     }
+                } else if let Some((ty, name)) = self.missing_proc_path(&prefab.path) {
+                    error(location, format!("undefined proc: {:?} on {}", name, ty))
+                        .register(self.context);
+                    Analysis::empty()
                 } else {
                     error(
                         location,
@@ -2340,9 +3625,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         "sleep" | "alert" | "shell" | "winexists" | "winget"
                     )
                 {
-                    self.env
-                        .sleeping_procs
-                        .insert_violator(self.proc_ref, unscoped_name, location);
+                    self.env.findings.sleeping_procs.insert_violator(
+                        self.proc_ref,
+                        unscoped_name,
+                        location,
+                    );
                 }
                 self.check_type_sleepers(self.ty, location, unscoped_name);
                 let src = self.ty;
@@ -2380,6 +3667,9 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
             Term::ParentCall(args) => {
                 self.calls_parent = true;
+                if args.is_empty() {
+                    self.forwards_args = true;
+                }
                 if let Some(proc) = self.proc_ref.parent_proc() {
                     // TODO: if args are empty, call w/ same args
                     let src = self.ty;
@@ -2418,6 +3708,19 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             Term::NewPrefab { prefab, args } => {
                 if let Some(nav) = self.ty.navigate_path(&prefab.path) {
                     // TODO: handle proc/verb paths here
+                    // Callbacks take the receiver and proc, then its arguments.
+                    if let Some(callback) = self.objtree.find("/datum/callback")
+                        && nav.ty().is_subtype_of(&callback)
+                        && let Some([receiver, reference, rest @ ..]) = args.as_deref()
+                    {
+                        self.check_proc_reference(
+                            location,
+                            Some(receiver),
+                            reference,
+                            rest,
+                            local_vars,
+                        );
+                    }
                     self.visit_new(location, nav.ty(), args, local_vars)
                 } else {
                     error(
@@ -2443,9 +3746,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 in_list,
             } => {
                 if self.inside_newcontext == 0 {
-                    self.env
-                        .sleeping_procs
-                        .insert_violator(self.proc_ref, "input", location);
+                    self.env.findings.sleeping_procs.insert_violator(
+                        self.proc_ref,
+                        "input",
+                        location,
+                    );
                 }
                 // TODO: deal with in_list
                 self.visit_arguments(location, args, local_vars);
@@ -2490,12 +3795,14 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     self.visit_expression(location, expr, None, local_vars);
                 }
 
-                if args.len() == 3 {
+                let mut analysis: Analysis = if args.len() == 3 {
                     // X,Y,Z - it's gotta be a turf
                     assumption_set![Assumption::IsType(true, self.objtree.expect("/turf"))].into()
                 } else {
                     Analysis::empty()
-                }
+                };
+                analysis.maybe_null = Some("locate()".to_owned());
+                analysis
             }
             Term::Pick(choices) => {
                 for (weight, choice) in choices.iter() {
@@ -2509,8 +3816,22 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                 Analysis::empty()
             }
             Term::DynamicCall(lhs_args, rhs_args) => {
+                self.opaque_calls += 1;
                 self.visit_arguments(location, lhs_args, local_vars);
                 self.visit_arguments(location, rhs_args, local_vars);
+                match &lhs_args[..] {
+                    [reference] => {
+                        self.check_proc_reference(location, None, reference, rhs_args, local_vars)
+                    }
+                    [receiver, reference] => self.check_proc_reference(
+                        location,
+                        Some(receiver),
+                        reference,
+                        rhs_args,
+                        local_vars,
+                    ),
+                    _ => {}
+                }
                 Analysis::empty() // TODO
             }
             Term::ExternalCall {
@@ -2539,6 +3860,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     value: Some(Constant::Prefab(Box::new(pop))),
                     fix_hint: None,
                     is_impure: None,
+                    maybe_null: None,
                 }
             }
             Term::__PROC__ => {
@@ -2564,6 +3886,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     value: Some(Constant::Prefab(Box::new(pop))),
                     fix_hint: None,
                     is_impure: None,
+                    maybe_null: None,
                 }
             }
         }
@@ -2601,13 +3924,224 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         assumption_set![Assumption::IsType(true, typepath)].into()
     }
 
+    /// Report `value` if it's known to be a type unrelated to `declared`.
+    /// Values which might be a subtype are allowed, as are unknown types.
+    fn check_type_mismatch(
+        &mut self,
+        location: Location,
+        declared: Option<TypeRef<'o>>,
+        value: &Analysis<'o>,
+        errortype: &'static str,
+        describe: impl FnOnce(TypeRef<'o>, TypeRef<'o>) -> String,
+    ) {
+        if let Some(declared) = declared
+            && let Some(actual) = value.known_type()
+            && !actual.is_subtype_of(&declared)
+            && !declared.is_subtype_of(&actual)
+        {
+            error(location, describe(declared, actual))
+                .set_severity(Severity::Warning)
+                .with_errortype(errortype)
+                .register(self.context);
+        }
+    }
+
+    /// Report adding a value to a typed list which holds an unrelated type.
+    fn check_list_add(&mut self, location: Location, list: &StaticType<'o>, value: &Analysis<'o>) {
+        let StaticType::List { keys, .. } = list else {
+            return;
+        };
+        // Adding a list adds each of its elements instead.
+        let added = match &value.static_ty {
+            StaticType::List { keys, .. } => Analysis::from((**keys).clone()),
+            ty if ty.is_list() => return,
+            _ => value.clone(),
+        };
+        self.check_type_mismatch(
+            location,
+            keys.basic_type(),
+            &added,
+            "type_mismatch_list",
+            |declared, actual| {
+                format!("type mismatch: adding {} to a list of {}", actual, declared)
+            },
+        );
+    }
+
+    /// The explanation of a deprecated proc, formatted to follow a message.
+    fn deprecation_suffix(&self, deprecated: ProcRef<'o>) -> String {
+        match self.env.settings.deprecation_messages.get(&deprecated) {
+            Some(message) if !message.is_empty() => format!(": {}", message),
+            _ => String::new(),
+        }
+    }
+
+    /// Report a call to a deprecated proc, unless it comes from the
+    /// deprecated proc itself or one of its overrides.
+    fn check_deprecated_call(&self, location: Location, proc: ProcRef<'o>) {
+        let deprecated = &self.env.settings.deprecated;
+        if let Some((target, true, decllocation)) = deprecated.get_self_or_parent(proc)
+            && deprecated
+                .get_self_or_parent(self.proc_ref)
+                .is_none_or(|(ours, ..)| ours != target)
+        {
+            error(
+                location,
+                format!(
+                    "{} is deprecated{}",
+                    target,
+                    self.deprecation_suffix(target)
+                ),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("deprecated")
+            .with_note(decllocation, "deprecated here")
+            .register(self.context);
+        }
+    }
+
+    /// Report a use of a var declared `SpacemanDMM_deprecated`.
+    fn check_deprecated_var(
+        &self,
+        location: Location,
+        ty: TypeRef<'o>,
+        name: &str,
+        decl: &VarDeclaration,
+    ) {
+        if decl.var_type.flags.is_deprecated() {
+            error(location, format!("var {:?} on {} is deprecated", name, ty))
+                .set_severity(Severity::Warning)
+                .with_errortype("deprecated")
+                .with_note(decl.location, "deprecated here")
+                .register(self.context);
+        }
+    }
+
+    /// Report a `[[lints]]` rule being broken, if the rule applies here.
+    fn register_lint(&self, location: Location, rule: &LintRule, desc: String) {
+        let Some(severity) = rule.severity() else {
+            return;
+        };
+        if rule.verbs_only
+            && !self
+                .proc_ref
+                .get_declaration()
+                .is_some_and(|decl| decl.kind.is_verb())
+        {
+            return;
+        }
+        let matches = |pattern: &String| self.lint_type_matches(self.ty, pattern);
+        if !(rule.types.is_empty() || rule.types.iter().any(matches))
+            || rule.exclude_types.iter().any(matches)
+        {
+            return;
+        }
+        let desc = match rule.message.as_ref() {
+            Some(message) => format!("{}: {}", desc, message),
+            None => desc,
+        };
+        error(location, desc)
+            .set_severity(severity)
+            .with_errortype(rule.errortype.0)
+            .register(self.context);
+    }
+
+    /// Whether `ty` is the type `pattern` names or one of its subtypes.
+    fn lint_type_matches(&self, ty: TypeRef<'o>, pattern: &str) -> bool {
+        self.objtree
+            .find(pattern.trim_end_matches('/'))
+            .is_some_and(|found| ty.is_subtype_of(found.get()))
+    }
+
+    /// Check `forbid_call` rules. `type_matches` decides whether a target
+    /// qualified by a typepath refers to this proc.
+    fn check_call_lints(
+        &self,
+        location: Location,
+        name: &str,
+        type_matches: impl Fn(&str) -> bool,
+    ) {
+        let config = self.context.config();
+        for rule in config.lints.iter() {
+            let Some(target) = rule.forbid_call.as_deref() else {
+                continue;
+            };
+            let (path, target_name) = LintRule::split_target(target);
+            if target_name == name && path.is_none_or(&type_matches) {
+                self.register_lint(location, rule, format!("call to {} is forbidden", target));
+            }
+        }
+    }
+
+    /// Check `forbid_read` and `forbid_write` rules for a var on `ty`, or for
+    /// a builtin local like `usr` if there is no type.
+    fn check_var_lints(&self, location: Location, ty: Option<TypeRef<'o>>, name: &str) {
+        let reads = self.assign_target != Some(AssignOp::Assign);
+        let writes = self.assign_target.is_some();
+        let config = self.context.config();
+        for rule in config.lints.iter() {
+            for (target, applies, verb) in [
+                (rule.forbid_read.as_deref(), reads, "reading"),
+                (rule.forbid_write.as_deref(), writes, "writing"),
+            ] {
+                let Some(target) = target.filter(|_| applies) else {
+                    continue;
+                };
+                let (path, target_name) = LintRule::split_target(target);
+                let type_matches = |path| ty.is_some_and(|ty| self.lint_type_matches(ty, path));
+                if target_name == name && path.is_none_or(type_matches) {
+                    self.register_lint(location, rule, format!("{} {} is forbidden", verb, target));
+                }
+            }
+        }
+    }
+
+    /// Check `require_parent_call` rules for a proc which never calls `..()`.
+    fn check_parent_call_lints(&self) {
+        let config = self.context.config();
+        for rule in config.lints.iter() {
+            let Some(target) = rule.require_parent_call.as_deref() else {
+                continue;
+            };
+            let (path, target_name) = LintRule::split_target(target);
+            if target_name == self.proc_ref.name()
+                && path.is_none_or(|path| self.lint_type_matches(self.ty, path))
+            {
+                self.register_lint(
+                    self.proc_ref.location,
+                    rule,
+                    format!("{} must call ..()", self.proc_ref),
+                );
+            }
+        }
+    }
+
+    fn check_null_deref(&mut self, location: Location, lhs: &Analysis<'o>, what: &str, name: &str) {
+        if let Some(source) = lhs.maybe_null.as_ref() {
+            error(
+                location,
+                format!(
+                    "{} on a value which may be null from {}: {:?}",
+                    what, source, name
+                ),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("possible_null_deref")
+            .with_note(
+                location,
+                "check it with `if` or `istype()` first, or use `?.`",
+            )
+            .register(self.context);
+        }
+    }
+
     fn check_type_sleepers(&mut self, ty: TypeRef<'o>, location: Location, unscoped_name: &str) {
         match ty.get().path.as_str() {
             "/client" => {
                 if self.inside_newcontext == 0
                     && matches!(unscoped_name, "SoundQuery" | "MeasureText")
                 {
-                    self.env.sleeping_procs.insert_violator(
+                    self.env.findings.sleeping_procs.insert_violator(
                         self.proc_ref,
                         format!("client.{}", unscoped_name).as_str(),
                         location,
@@ -2616,7 +4150,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
             "/world" => {
                 if self.inside_newcontext == 0 && matches!(unscoped_name, "Import" | "Export") {
-                    self.env.sleeping_procs.insert_violator(
+                    self.env.findings.sleeping_procs.insert_violator(
                         self.proc_ref,
                         format!("world.{}", unscoped_name).as_str(),
                         location,
@@ -2641,6 +4175,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             Follow::Field(PropertyAccessKind::SafeColon, _) => Analysis::empty(),
             Follow::Call(PropertyAccessKind::Colon, _, args)
             | Follow::Call(PropertyAccessKind::SafeColon, _, args) => {
+                self.opaque_calls += 1;
                 // No analysis yet, but be sure to visit the arguments
                 for arg in args.iter() {
                     let mut argument_value = arg;
@@ -2664,11 +4199,13 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
 
             Follow::Index(_, expr) => {
-                self.visit_expression(location, expr, None, local_vars);
-                // TODO: differentiate between L[1] and L[non_numeric_key]
-                match lhs.static_ty {
-                    StaticType::List { keys, .. } => {
-                        let mut res = Analysis::from(*keys);
+                let index = self.visit_expression(location, expr, None, local_vars);
+                // Text and objects look up associated values, numbers elements.
+                let by_key = index.known_type().is_some()
+                    || index.aset.set.contains(&Assumption::IsText(true));
+                let mut res = match lhs.static_ty {
+                    StaticType::List { keys, values, .. } => {
+                        let mut res = Analysis::from(if by_key { *values } else { *keys });
                         if let Some((loc, _)) = lhs.fix_hint {
                             res.fix_hint =
                                 Some((loc, "add a type annotation after /list here".to_owned()))
@@ -2676,11 +4213,18 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         res
                     }
                     _ => lhs.clone(), // carry through fix_hint
-                }
+                };
+                res.maybe_null = Some("a list index".to_owned());
+                res
             }
             Follow::Field(kind, name) => {
+                if *kind == PropertyAccessKind::Dot {
+                    self.check_null_deref(location, &lhs, "field access", name);
+                }
                 if let Some(ty) = lhs.static_ty.basic_type() {
                     if let Some(decl) = ty.get_var_declaration(name) {
+                        self.check_var_lints(location, Some(ty), name);
+                        self.check_deprecated_var(location, ty, name, decl);
                         if ty != self.ty && decl.var_type.flags.is_private() {
                             error(
                                 location,
@@ -2781,11 +4325,14 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
 
             Follow::Call(kind, name, arguments) => {
+                if *kind == PropertyAccessKind::Dot {
+                    self.check_null_deref(location, &lhs, "proc call", name);
+                }
                 if let Some(ty) = lhs.static_ty.basic_type() {
                     self.check_type_sleepers(ty, location, name);
                     if let Some(proc) = ty.get_proc(name) {
                         if let Some((privateproc, true, decllocation)) =
-                            self.env.private.get_self_or_parent(proc)
+                            self.env.settings.private.get_self_or_parent(proc)
                             && ty != privateproc.ty()
                         {
                             error(
@@ -2801,7 +4348,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                             return Analysis::empty(); // dont double up with visit_call()
                         }
                         if let Some((protectedproc, true, decllocation)) =
-                            self.env.protected.get_self_or_parent(proc)
+                            self.env.settings.protected.get_self_or_parent(proc)
                             && !self.ty.is_subtype_of(protectedproc.ty().get())
                         {
                             error(
@@ -2815,6 +4362,9 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                             .with_note(decllocation, "prohibited by this protected_proc annotation")
                             .register(self.context);
                         }
+                        if name == "Add" {
+                            self.list_add = Some(lhs.static_ty.clone());
+                        }
                         self.visit_call(location, ty, proc, arguments, false, local_vars)
                     } else {
                         error(location, format!("undefined proc: {:?} on {}", name, ty))
@@ -2822,6 +4372,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                         Analysis::empty()
                     }
                 } else {
+                    self.opaque_calls += 1;
                     error(
                         location,
                         format!("proc call requires static type: {:?}", name),
@@ -2890,6 +4441,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                     value: Some(Constant::Prefab(Box::new(path_const))),
                     fix_hint: None,
                     is_impure: None,
+                    maybe_null: None,
                 }
             }
         }
@@ -2906,7 +4458,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         if let Some(impurity) = rhs.is_impure
             && impurity
         {
-            self.env.impure_procs.insert_violator(
+            self.env.findings.impure_procs.insert_violator(
                 self.proc_ref,
                 &format!("{} done on non-local var", operator),
                 location,
@@ -2950,6 +4502,13 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             UnaryOp::PreDecr | UnaryOp::PostDecr => {
                 self.check_operator_overload(rhs, location, "operator--", local_vars)
             }
+            // Fold negative literals so they can be checked like any other.
+            UnaryOp::Neg => match rhs.value {
+                Some(Constant::Float(f)) => {
+                    Analysis::from_value(self.objtree, Constant::Float(-f), None)
+                }
+                _ => Analysis::empty(),
+            },
             /*
             (UnaryOp::Neg, Type::Number) => Type::Number.into(),
             (UnaryOp::BitNot, Type::Number) => Type::Number.into(),
@@ -2989,6 +4548,30 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         }
     }
 
+    fn check_constant_op(&mut self, location: Location, warning: Option<(String, &'static str)>) {
+        if let Some((desc, errortype)) = warning {
+            error(location, desc)
+                .set_severity(Severity::Warning)
+                .with_errortype(errortype)
+                .register(self.context);
+        }
+    }
+
+    fn check_constant_binary(
+        &mut self,
+        location: Location,
+        op: BinaryOp,
+        (lhs, lty): (&Expression, &Analysis<'o>),
+        (rhs, rty): (&Expression, &Analysis<'o>),
+    ) {
+        let lhs = literal_operand(location, lhs, lty);
+        let rhs = literal_operand(location, rhs, rty);
+        self.check_constant_op(
+            location,
+            dm::constants::check_binary_op(op, lhs.as_ref(), rhs.as_ref()),
+        );
+    }
+
     fn visit_binary(&mut self, lhs: Analysis<'o>, rhs: Analysis<'o>, op: BinaryOp) -> Analysis<'o> {
         //println!("visit_binary: don't know anything about {}", op);
         if lhs.static_ty.is_list() {
@@ -3109,12 +4692,23 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         is_exact: bool,
         local_vars: &mut HashMap<String, LocalVar<'o>, RandomState>,
     ) -> Analysis<'o> {
-        self.env.call_tree.entry(self.proc_ref).or_default().push((
-            proc,
-            location,
-            self.inside_newcontext != 0,
-        ));
-        if let Some((privateproc, true, decllocation)) = self.env.private.get_self_or_parent(proc)
+        // Taken first, so calls among the arguments don't see it.
+        let list_add = self.list_add.take();
+        self.env
+            .findings
+            .call_tree
+            .entry(self.proc_ref)
+            .or_default()
+            .push((proc, location, self.inside_newcontext != 0));
+        self.check_deprecated_call(location, proc);
+        // Recursing or calling `..()` is not a new use of the proc.
+        if !(src == self.ty && proc.name() == self.proc_ref.name()) {
+            self.check_call_lints(location, proc.name(), |path| {
+                self.lint_type_matches(src, path) || self.lint_type_matches(proc.ty(), path)
+            });
+        }
+        if let Some((privateproc, true, decllocation)) =
+            self.env.settings.private.get_self_or_parent(proc)
             && self.ty != privateproc.ty()
         {
             error(
@@ -3183,6 +4777,7 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
                                 // calling /datum/foo() on a /datum/A won't
                                 // complain about /datum/B/foo().
                                 self.env
+                                    .findings
                                     .used_kwargs
                                     .entry(format!("{}/proc/{}", src, proc.name()))
                                     .or_insert_with(|| KwargInfo {
@@ -3231,6 +4826,34 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             }
 
             let analysis = self.visit_expression(location, argument_value, None, local_vars);
+            if let Some(list) = &list_add
+                && this_kwarg.is_none()
+            {
+                self.check_list_add(location, list, &analysis);
+            }
+            if !proc.is_builtin() && !arglist_used {
+                let param = match this_kwarg {
+                    Some(kw) => proc.parameters.iter().find(|p| p.name == *kw),
+                    None => proc.parameters.get(param_idx),
+                };
+                if let Some(param) = param
+                    && let Ok(declared) =
+                        static_type(self.objtree, location, &param.var_type.type_path)
+                {
+                    self.check_type_mismatch(
+                        location,
+                        declared.basic_type(),
+                        &analysis,
+                        "type_mismatch_argument",
+                        |declared, actual| {
+                            format!(
+                                "type mismatch: passing {} as {} to {}, which expects a {}",
+                                actual, param.name, proc, declared
+                            )
+                        },
+                    );
+                }
+            }
             if let Some(kw) = this_kwarg {
                 param_name_map.insert(kw.as_str(), analysis);
                 param_expr_map.insert(kw.as_str(), argument_value);
@@ -3326,8 +4949,12 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         }
 
         if proc.ty().is_root() && proc.is_builtin() {
-            Analysis::from(self.global_builtin_returntype(proc))
-        } else if let Some(return_type) = self.env.return_type.get(&proc) {
+            let mut analysis = Analysis::from(self.global_builtin_returntype(proc));
+            if NULLABLE_BUILTINS.contains(&proc.name()) {
+                analysis.maybe_null = Some(format!("{}()", proc.name()));
+            }
+            analysis
+        } else if let Some(return_type) = self.env.settings.return_type.get(&proc) {
             let ec = type_expr::TypeExprContext {
                 objtree: self.objtree,
                 param_name_map,
@@ -3336,7 +4963,11 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
             match return_type.evaluate(location, &ec) {
                 Ok(st) => {
                     let hint = format!("return type evaluated to {:?}", st);
-                    Analysis::from(st).with_fix_hint(location, hint)
+                    let mut analysis = Analysis::from(st).with_fix_hint(location, hint);
+                    if return_type.is_nullable() {
+                        analysis.maybe_null = Some(format!("{}()", proc));
+                    }
+                    analysis
                 }
                 Err(err) => {
                     err.with_component(dm::Component::DreamChecker)
@@ -3379,8 +5010,133 @@ impl<'o, 's> AnalyzeProc<'o, 's> {
         }
     }
 
+    /// If `path` names a proc which doesn't exist on a type which does,
+    /// return that type and the proc's name.
+    fn missing_proc_path<'p>(&self, path: &'p [(PathOp, Ident)]) -> Option<(TypeRef<'o>, &'p str)> {
+        let idx = path
+            .iter()
+            .position(|(_, piece)| ProcDeclKind::from_name(piece).is_some())?;
+        let (_, name) = path.get(idx + 1)?;
+        let ty = if idx == 0 && path[0].0 == PathOp::Slash {
+            self.objtree.root()
+        } else {
+            self.ty.navigate_path(&path[..idx])?.ty()
+        };
+        Some((ty, name.as_str()))
+    }
+
+    /// Check a proc reference passed to `call()()` or a callback, along with
+    /// the arguments it will be called with.
+    fn check_proc_reference(
+        &mut self,
+        location: Location,
+        receiver: Option<&'o Expression>,
+        reference: &'o Expression,
+        args: &'o [Expression],
+        local_vars: &HashMap<String, LocalVar<'o>, RandomState>,
+    ) {
+        // Only receivers whose type is evident without evaluation are used.
+        let receiver_ty = match receiver.and_then(Expression::as_term) {
+            Some(Term::Ident(name)) => local_vars
+                .get(name)
+                .and_then(|var| var.analysis.known_type()),
+            _ => None,
+        };
+
+        let proc = match proc_reference_target(reference) {
+            Some(ProcReferenceTarget::Path(path)) => {
+                let Some(NavigatePathResult::ProcPath(proc, _)) = self.ty.navigate_path(path)
+                else {
+                    // Unresolvable paths are reported when visiting them.
+                    return;
+                };
+                match receiver_ty {
+                    Some(ty) => match ty.get_proc(proc.name()) {
+                        Some(proc) => proc,
+                        None => {
+                            error(
+                                location,
+                                format!("undefined proc: {:?} on {}", proc.name(), ty),
+                            )
+                            .set_severity(Severity::Warning)
+                            .with_errortype("proc_ref_undefined")
+                            .register(self.context);
+                            return;
+                        }
+                    },
+                    None => proc,
+                }
+            }
+            Some(ProcReferenceTarget::Name(name)) => {
+                let Some(ty) = receiver_ty else {
+                    return;
+                };
+                match ty.get_proc(name) {
+                    Some(proc) => proc,
+                    None => {
+                        error(location, format!("undefined proc: {:?} on {}", name, ty))
+                            .set_severity(Severity::Warning)
+                            .with_errortype("proc_ref_undefined")
+                            .register(self.context);
+                        return;
+                    }
+                }
+            }
+            None => return,
+        };
+
+        let mut positional = 0;
+        for arg in args.iter() {
+            if let Expression::AssignOp {
+                op: AssignOp::Assign,
+                lhs,
+                ..
+            } = arg
+                && let Some(Term::Ident(name) | Term::String(name)) = lhs.as_term()
+            {
+                if !proc.parameters.iter().any(|p| p.name == *name) {
+                    error(
+                        location,
+                        format!("bad keyword argument {:?} to {}", name, proc),
+                    )
+                    .set_severity(Severity::Warning)
+                    .with_errortype("proc_ref_arguments")
+                    .with_note(proc.location, format!("{} is defined here", proc))
+                    .register(self.context);
+                }
+            } else if let Some(Term::Call(name, _)) = arg.as_term()
+                && name.as_str() == "arglist"
+            {
+                return;
+            } else {
+                positional += 1;
+            }
+        }
+        if positional > proc.parameters.len() && !proc.is_varargs() {
+            error(
+                location,
+                format!(
+                    "too many arguments to {}: expected at most {}, got {}",
+                    proc,
+                    proc.parameters.len(),
+                    positional
+                ),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("proc_ref_arguments")
+            .with_note(proc.location, format!("{} is defined here", proc))
+            .register(self.context);
+        }
+    }
+
     fn static_type(&mut self, location: Location, of: &[String]) -> Analysis<'o> {
-        Analysis::from(self.env.static_type(location, of))
+        match static_type(self.objtree, location, of) {
+            Ok(s) => Analysis::from(s),
+            Err(e) => {
+                e.register(self.context);
+                Analysis::from(StaticType::None)
+            }
+        }
     }
 
     fn global_builtin_returntype(&mut self, proc: ProcRef) -> StaticType<'o> {
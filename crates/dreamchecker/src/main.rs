@@ -5,6 +5,14 @@ use dreammaker as dm;
 #[macro_use]
 extern crate serde_json;
 
+mod baseline;
+mod changes;
+mod output;
+
+use baseline::{Baseline, Enclosing};
+use changes::Changes;
+use output::OutputFormat;
+
 // ----------------------------------------------------------------------------
 // Command-line interface
 
@@ -14,6 +22,14 @@ fn main() {
     let mut config_file = None;
     let mut json = false;
     let mut parse_only = false;
+    let mut output_format = OutputFormat::Text;
+    let mut baseline_file = None;
+    let mut write_baseline_file = None;
+    let mut changed_since = None;
+    let mut recursion_report_file = None;
+    let mut jobs = 1;
+    let mut check_icons = false;
+    let mut check_resources = false;
 
     let mut args = std::env::args();
     let _ = args.next(); // skip executable name
@@ -37,6 +53,48 @@ fn main() {
             config_file = Some(args.next().expect("must specify a file for -c"));
         } else if arg == "--json" {
             json = true;
+        } else if arg == "--output-format" {
+            let name = args
+                .next()
+                .expect("must specify a value for --output-format");
+            output_format = match OutputFormat::from_name(&name) {
+                Some(format) => format,
+                None => {
+                    eprintln!("unknown output format: {}", name);
+                    eprintln!("expected one of: text, json, sarif, github");
+                    return;
+                }
+            };
+        } else if arg == "--baseline" {
+            baseline_file = Some(args.next().expect("must specify a file for --baseline"));
+        } else if arg == "--write-baseline" {
+            write_baseline_file = Some(
+                args.next()
+                    .expect("must specify a file for --write-baseline"),
+            );
+        } else if arg == "--changed-since" {
+            changed_since = Some(
+                args.next()
+                    .expect("must specify a revision for --changed-since"),
+            );
+        } else if arg == "--recursion-report" {
+            recursion_report_file = Some(
+                args.next()
+                    .expect("must specify a file for --recursion-report"),
+            );
+        } else if arg == "--jobs" {
+            let value = args.next().expect("must specify a value for --jobs");
+            jobs = match value.parse() {
+                Ok(jobs) => jobs,
+                Err(_) => {
+                    eprintln!("invalid value for --jobs: {}", value);
+                    return;
+                }
+            };
+        } else if arg == "--check-icons" {
+            check_icons = true;
+        } else if arg == "--check-resources" {
+            check_resources = true;
         } else if arg == "--parse-only" {
             parse_only = true;
         } else {
@@ -45,6 +103,13 @@ fn main() {
         }
     }
 
+    if json && output_format != OutputFormat::Text {
+        eprintln!(
+            "--json prints counts to standard output, and cannot be combined with --output-format"
+        );
+        return;
+    }
+
     let dme = environment
         .map(std::path::PathBuf::from)
         .unwrap_or_else(|| {
@@ -53,41 +118,159 @@ fn main() {
                 .expect("no .dme found")
         });
 
+    // 0 selects the number of threads automatically, and 1 is serial.
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()
+        .expect("failed to initialize thread pool");
+    let parallel = jobs != 1;
+
     let mut context = dm::Context::default();
     if let Some(filepath) = config_file {
         context.force_config(filepath.as_ref());
     } else {
         context.autodetect_config(&dme);
     }
-    context.set_print_severity(Some(dm::Severity::Info));
+    // Keep stdout clean for machine-readable formats.
+    let machine_readable = output_format != OutputFormat::Text;
+    // With a baseline, diagnostics can only be printed once they're filtered.
+    let print_immediately = !machine_readable
+        && baseline_file.is_none()
+        && write_baseline_file.is_none()
+        && changed_since.is_none();
+    if print_immediately {
+        context.set_print_severity(Some(dm::Severity::Info));
+    }
+
+    macro_rules! progress {
+        ($($rest:tt)*) => {
+            if machine_readable { eprintln!($($rest)*) } else { println!($($rest)*) }
+        }
+    }
 
-    println!("============================================================");
-    println!("Parsing {}...\n", dme.display());
-    let pp = dm::preprocessor::Preprocessor::new(&context, dme).expect("i/o error opening .dme");
-    let indents = dm::indents::IndentProcessor::new(&context, pp);
-    let mut parser = dm::parser::Parser::new(&context, indents);
-    parser.enable_procs();
-    let (fatal_errored, tree) = parser.parse_object_tree_2();
+    progress!("============================================================");
+    progress!("Parsing {}...\n", dme.display());
+    let icons_root = dme.parent().map(ToOwned::to_owned).unwrap_or_default();
+    let mut pp =
+        dm::preprocessor::Preprocessor::new(&context, dme.clone()).expect("i/o error opening .dme");
+    let (fatal_errored, tree) = {
+        let indents = dm::indents::IndentProcessor::new(&context, &mut pp);
+        let mut parser = dm::parser::Parser::new(&context, indents);
+        parser.enable_procs();
+        parser.parse_object_tree_2()
+    };
+    let define_history = pp.finalize();
 
     if !parse_only && !fatal_errored {
-        dreamchecker::run_cli(&context, &tree);
+        let report = if parallel {
+            dreamchecker::run_parallel(&context, &tree, !machine_readable)
+        } else if machine_readable {
+            dreamchecker::run(&context, &tree)
+        } else {
+            dreamchecker::run_cli(&context, &tree)
+        };
+        if let Some(path) = &recursion_report_file {
+            let mut file = std::io::BufWriter::new(
+                std::fs::File::create(path).expect("error creating recursion report"),
+            );
+            output::write_recursion_report(&mut file, &context, &report.recursion_cycles)
+                .expect("error writing recursion report");
+            progress!(
+                "Wrote {} recursive cycles to {}",
+                report.recursion_cycles.len(),
+                path
+            );
+        }
+        if check_icons {
+            dreamchecker::icons::check_icons(&context, &tree, &icons_root);
+        }
+        if check_resources {
+            let file_dirs = dreamchecker::resources::file_dirs(&define_history);
+            dreamchecker::resources::check_resources(&context, &tree, &dme, &file_dirs);
+        }
+        context.check_unused_suppressions();
     }
 
-    println!("============================================================");
-    let errors = context
-        .errors()
+    progress!("============================================================");
+    let all_errors = context.errors();
+    let mut reported: Vec<&dm::DMError> = all_errors
         .iter()
         .filter(|each| each.severity() <= dm::Severity::Info)
-        .count();
-    println!("Found {} diagnostics", errors);
+        .collect();
+
+    if let Some(path) = write_baseline_file {
+        let enclosing = Enclosing::new(&tree);
+        let mut fingerprints: Vec<String> = reported
+            .iter()
+            .map(|error| baseline::fingerprint(&enclosing, error))
+            .collect();
+        Baseline::write(path.as_ref(), &mut fingerprints).expect("error writing baseline");
+        progress!(
+            "Wrote {} diagnostics to baseline {}",
+            fingerprints.len(),
+            path
+        );
+        return;
+    }
+
+    if let Some(rev) = changed_since {
+        let enclosing = Enclosing::new(&tree);
+        let changes =
+            Changes::since(&context, &enclosing, &dme, &rev).expect("error reading git diff");
+        let total = reported.len();
+        reported.retain(|error| changes.is_relevant(&enclosing, error));
+        progress!(
+            "Ignoring {} diagnostics on lines unchanged since {}",
+            total - reported.len(),
+            rev
+        );
+    }
+
+    if let Some(path) = baseline_file {
+        let mut baseline = Baseline::read(path.as_ref()).expect("error reading baseline");
+        let enclosing = Enclosing::new(&tree);
+        let total = reported.len();
+        reported.retain(|error| !baseline.take(&baseline::fingerprint(&enclosing, error)));
+        progress!(
+            "Ignoring {} diagnostics present in baseline {}",
+            total - reported.len(),
+            path
+        );
+    }
+
+    if !print_immediately && !machine_readable {
+        let stderr = &mut std::io::stderr().lock();
+        for error in reported.iter() {
+            context
+                .pretty_print_error_nocolor(stderr, error)
+                .expect("error writing to stderr");
+        }
+    }
+
+    let errors = reported.len();
+    progress!("Found {} diagnostics", errors);
+
+    output_format
+        .write(&mut std::io::stdout().lock(), &context, &reported)
+        .expect("error writing diagnostics");
 
     if json {
-        serde_json::to_writer(std::io::stdout().lock(), &json! {{
-            "hint": context.errors().iter().filter(|each| each.severity() == dm::Severity::Hint).count(),
-            "info": context.errors().iter().filter(|each| each.severity() == dm::Severity::Info).count(),
-            "warning": context.errors().iter().filter(|each| each.severity() == dm::Severity::Warning).count(),
-            "error": context.errors().iter().filter(|each| each.severity() == dm::Severity::Error).count(),
-        }}).unwrap();
+        let count = |severity| {
+            reported
+                .iter()
+                .filter(|each| each.severity() == severity)
+                .count()
+        };
+        serde_json::to_writer(
+            std::io::stdout().lock(),
+            &json! {{
+                "hint": count(dm::Severity::Hint),
+                "info": count(dm::Severity::Info),
+                "warning": count(dm::Severity::Warning),
+                "error": count(dm::Severity::Error),
+            }},
+        )
+        .unwrap();
     }
 
     std::process::exit(if errors > 0 { 1 } else { 0 });
@@ -0,0 +1,385 @@
+//! Machine-readable diagnostic output for the command-line interface.
+
+use std::io::{self, Write};
+
+use dreamchecker::RecursionCycle;
+use dreammaker::{Context, DMError, Location, Severity};
+use serde_json::Value;
+
+/// The formats accepted by `--output-format`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Coloured human-readable text on stderr.
+    Text,
+    /// One JSON object per diagnostic per line.
+    Json,
+    /// A SARIF 2.1.0 log.
+    Sarif,
+    /// GitHub Actions workflow commands.
+    Github,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "sarif" => Some(OutputFormat::Sarif),
+            "github" => Some(OutputFormat::Github),
+            _ => None,
+        }
+    }
+
+    /// Write `errors` to `w` in this format.
+    pub fn write<W: Write>(
+        self,
+        w: &mut W,
+        context: &Context,
+        errors: &[&DMError],
+    ) -> io::Result<()> {
+        match self {
+            OutputFormat::Text => Ok(()),
+            OutputFormat::Json => {
+                for error in errors {
+                    serde_json::to_writer(&mut *w, &error_json(context, error))?;
+                    writeln!(w)?;
+                }
+                Ok(())
+            }
+            OutputFormat::Sarif => {
+                serde_json::to_writer_pretty(&mut *w, &sarif_log(context, errors))?;
+                writeln!(w)
+            }
+            OutputFormat::Github => {
+                for error in errors {
+                    write_github_annotation(w, context, error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn file_name(context: &Context, location: Location) -> String {
+    // Forward slashes keep paths stable across platforms for CI consumers.
+    context
+        .file_path(location.file)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn location_json(context: &Context, location: Location) -> Value {
+    if location.is_builtins() {
+        return Value::Null;
+    }
+    json!({
+        "file": file_name(context, location),
+        "line": location.line,
+        "column": location.column,
+    })
+}
+
+fn error_json(context: &Context, error: &DMError) -> Value {
+    json!({
+        "location": location_json(context, error.location()),
+        "severity": error.severity().to_string(),
+        "component": error.component().name(),
+        "errortype": error.errortype(),
+        "description": error.description(),
+        "notes": error.notes().iter().map(|note| json!({
+            "location": location_json(context, note.location()),
+            "description": note.description(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+// ----------------------------------------------------------------------------
+// SARIF
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "note",
+    }
+}
+
+fn sarif_location(context: &Context, location: Location) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": {
+                "uri": file_name(context, location),
+            },
+            "region": {
+                "startLine": location.line,
+                "startColumn": location.column.max(1),
+            },
+        },
+    })
+}
+
+fn sarif_log(context: &Context, errors: &[&DMError]) -> Value {
+    let mut rules: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error.errortype())
+        .collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results: Vec<Value> = errors
+        .iter()
+        .map(|error| {
+            let mut result = json!({
+                "level": sarif_level(error.severity()),
+                "message": { "text": error.description() },
+            });
+            if let Some(errortype) = error.errortype() {
+                result["ruleId"] = json!(errortype);
+            }
+            if !error.location().is_builtins() {
+                result["locations"] = json!([sarif_location(context, error.location())]);
+            }
+            let related: Vec<Value> = error
+                .notes()
+                .iter()
+                .filter(|note| !note.location().is_builtins())
+                .enumerate()
+                .map(|(id, note)| {
+                    let mut location = sarif_location(context, note.location());
+                    location["id"] = json!(id);
+                    location["message"] = json!({ "text": note.description() });
+                    location
+                })
+                .collect();
+            if !related.is_empty() {
+                result["relatedLocations"] = Value::Array(related);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "dreamchecker",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/SpaceManiac/SpacemanDMM",
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+// ----------------------------------------------------------------------------
+// GitHub Actions
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn write_github_annotation<W: Write>(
+    w: &mut W,
+    context: &Context,
+    error: &DMError,
+) -> io::Result<()> {
+    let command = match error.severity() {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "notice",
+    };
+
+    let mut properties = Vec::new();
+    let location = error.location();
+    if !location.is_builtins() {
+        properties.push(format!(
+            "file={}",
+            escape_property(&file_name(context, location))
+        ));
+        properties.push(format!("line={}", location.line));
+        properties.push(format!("col={}", location.column));
+    }
+    if let Some(errortype) = error.errortype() {
+        properties.push(format!("title={}", escape_property(errortype)));
+    }
+
+    let mut message = error.description().to_owned();
+    for note in error.notes() {
+        message.push_str("\n- ");
+        if note.location() != location && !note.location().is_builtins() {
+            message.push_str(&format!(
+                "{}:{}:{}: ",
+                file_name(context, note.location()),
+                note.location().line,
+                note.location().column
+            ));
+        }
+        message.push_str(note.description());
+    }
+
+    if properties.is_empty() {
+        writeln!(w, "::{}::{}", command, escape_data(&message))
+    } else {
+        writeln!(
+            w,
+            "::{} {}::{}",
+            command,
+            properties.join(","),
+            escape_data(&message)
+        )
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Recursion report
+
+/// Write the cycles found in the call graph as a JSON array.
+pub fn write_recursion_report<W: Write>(
+    w: &mut W,
+    context: &Context,
+    cycles: &[RecursionCycle],
+) -> io::Result<()> {
+    let cycles: Vec<Value> = cycles
+        .iter()
+        .map(|cycle| {
+            json!({
+                "procs": cycle.calls.iter().map(|(caller, _, _)| caller).collect::<Vec<_>>(),
+                "calls": cycle.calls.iter().map(|(caller, callee, location)| json!({
+                    "caller": caller,
+                    "callee": callee,
+                    "location": location_json(context, *location),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *w, &cycles)?;
+    writeln!(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dreammaker::Component;
+    use std::path::Path;
+
+    fn diagnostics(context: &Context) -> Vec<DMError> {
+        let file = context.register_file(Path::new("code\\door.dm"));
+        let at = |line, column| Location { file, line, column };
+        vec![
+            DMError::new(at(3, 5), "door, with 100% chance\nof sticking")
+                .with_component(Component::DreamChecker)
+                .set_severity(Severity::Warning)
+                .with_errortype("sticky_door")
+                .with_note(at(3, 5), "the door")
+                .with_note(at(1, 1), "defined here"),
+            DMError::new(Location::builtins(), "bad builtin").set_severity(Severity::Error),
+            DMError::new(at(8, 0), "stuck again")
+                .set_severity(Severity::Hint)
+                .with_errortype("sticky_door"),
+        ]
+    }
+
+    fn write(format: OutputFormat) -> String {
+        let context = Context::default();
+        let errors = diagnostics(&context);
+        let errors: Vec<&DMError> = errors.iter().collect();
+        let mut out = Vec::new();
+        format.write(&mut out, &context, &errors).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json_lines() {
+        let out = write(OutputFormat::Json);
+        let lines: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            json!({
+                "location": { "file": "code/door.dm", "line": 3, "column": 5 },
+                "severity": "warning",
+                "component": "dreamchecker",
+                "errortype": "sticky_door",
+                "description": "door, with 100% chance\nof sticking",
+                "notes": [
+                    {
+                        "location": { "file": "code/door.dm", "line": 3, "column": 5 },
+                        "description": "the door",
+                    },
+                    {
+                        "location": { "file": "code/door.dm", "line": 1, "column": 1 },
+                        "description": "defined here",
+                    },
+                ],
+            })
+        );
+        assert_eq!(lines[1]["location"], Value::Null);
+        assert_eq!(lines[1]["errortype"], Value::Null);
+    }
+
+    #[test]
+    fn sarif() {
+        let log: Value = serde_json::from_str(&write(OutputFormat::Sarif)).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{ "id": "sticky_door" }])
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[0]["ruleId"], "sticky_door");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "code/door.dm" },
+                "region": { "startLine": 3, "startColumn": 5 },
+            })
+        );
+        let related = results[0]["relatedLocations"].as_array().unwrap();
+        assert_eq!(related.len(), 2);
+        assert_eq!(related[1]["id"], 1);
+        assert_eq!(related[1]["message"]["text"], "defined here");
+
+        assert_eq!(results[1]["level"], "error");
+        assert!(results[1].get("ruleId").is_none());
+        assert!(results[1].get("locations").is_none());
+
+        assert_eq!(results[2]["level"], "note");
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            1
+        );
+    }
+
+    #[test]
+    fn github() {
+        assert_eq!(
+            write(OutputFormat::Github),
+            "::warning file=code/door.dm,line=3,col=5,title=sticky_door::\
+             door, with 100%25 chance%0Aof sticking%0A- the door%0A- code/door.dm:1:1: defined here\n\
+             ::error::bad builtin\n\
+             ::notice file=code/door.dm,line=8,col=0,title=sticky_door::stuck again\n"
+        );
+    }
+
+    #[test]
+    fn github_escapes_properties() {
+        assert_eq!(escape_property("a:b,c%d\ne"), "a%3Ab%2Cc%25d%0Ae");
+    }
+}
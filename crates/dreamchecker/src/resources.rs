@@ -0,0 +1,330 @@
+//! Checking that resource literals such as `'sound/foo.ogg'` name files which
+//! exist, with the same case as on disk.
+//!
+//! DreamMaker searches for resources relative to the file which refers to
+//! them, the directory of the `.dme`, and each `FILE_DIR`. Windows does not
+//! care about case, but DreamDaemon on Linux does, so names are compared one
+//! component at a time rather than trusting the filesystem.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ahash::RandomState;
+use dm::ast::*;
+use dm::constants::Constant;
+use dm::lexer::Token;
+use dm::objtree::ObjectTree;
+use dm::preprocessor::DefineHistory;
+use dm::{Context, DMError, Location, Severity};
+use dreammaker as dm;
+
+use crate::walk::{Walker, walk_block};
+
+/// File extensions considered assets by the unreferenced resource report.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "dmi", "png", "jpg", "jpeg", "gif", "bmp", "ogg", "wav", "mp3", "mid", "midi", "ttf",
+];
+
+/// Collect the `FILE_DIR` search paths defined anywhere in the environment.
+pub fn file_dirs(history: &DefineHistory) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for (_, (name, define)) in history.iter() {
+        if name != "FILE_DIR" {
+            continue;
+        }
+        let mut dir = String::new();
+        for token in define.substitution() {
+            match token {
+                Token::String(text) => dir.push_str(text),
+                other => dir.push_str(&other.to_string()),
+            }
+        }
+        let dir = PathBuf::from(dir.replace('\\', "/"));
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Check resource literals in `objtree` against the files around
+/// `environment`, the path of the `.dme`.
+pub fn check_resources(
+    context: &Context,
+    objtree: &ObjectTree,
+    environment: &Path,
+    file_dirs: &[PathBuf],
+) {
+    let env_dir = environment.parent().unwrap_or(Path::new(""));
+    let mut checker = ResourceChecker {
+        context,
+        env_dir,
+        env_file: context.get_file(environment),
+        file_dirs,
+        listings: Default::default(),
+        referenced: Default::default(),
+    };
+
+    for ty in objtree.iter_types() {
+        for var in ty.vars.values() {
+            if let Some(constant) = var.value.constant.as_ref() {
+                checker.visit_constant(var.value.location, constant);
+            }
+        }
+        for proc in ty.procs.values() {
+            for value in proc.value.iter() {
+                if let Some(code) = value.code.as_ref() {
+                    walk_block(&mut checker, code);
+                }
+            }
+        }
+    }
+
+    checker.check_unreferenced();
+}
+
+fn error<S: Into<String>>(location: Location, desc: S) -> DMError {
+    DMError::new(location, desc).with_component(dm::Component::DreamChecker)
+}
+
+/// The result of looking for a path one component at a time.
+enum Lookup {
+    Exact(PathBuf),
+    /// Found, but only by ignoring case. Holds the name as it is on disk.
+    WrongCase(PathBuf, String),
+    Missing,
+}
+
+struct ResourceChecker<'a> {
+    context: &'a Context,
+    env_dir: &'a Path,
+    env_file: Option<dm::FileId>,
+    file_dirs: &'a [PathBuf],
+    /// The names in each directory, or `None` if it can't be read.
+    listings: HashMap<PathBuf, Option<Vec<String>>, RandomState>,
+    /// Files which something refers to.
+    referenced: HashSet<PathBuf, RandomState>,
+}
+
+impl ResourceChecker<'_> {
+    fn listing(&mut self, dir: &Path) -> Option<&[String]> {
+        self.listings
+            .entry(dir.to_owned())
+            .or_insert_with(|| {
+                let entries = std::fs::read_dir(if dir.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    dir
+                })
+                .ok()?;
+                Some(
+                    entries
+                        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                        .collect(),
+                )
+            })
+            .as_deref()
+    }
+
+    fn lookup_in(&mut self, base: &Path, relative: &str) -> Lookup {
+        let mut actual = base.to_owned();
+        let mut on_disk = Vec::new();
+        let mut wrong_case = false;
+        for component in relative.split('/').filter(|c| !c.is_empty() && *c != ".") {
+            if component == ".." {
+                actual.pop();
+                on_disk.push(component.to_owned());
+                continue;
+            }
+            let Some(listing) = self.listing(&actual) else {
+                return Lookup::Missing;
+            };
+            let name = if listing.iter().any(|name| name == component) {
+                component.to_owned()
+            } else if let Some(name) = listing
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(component))
+            {
+                wrong_case = true;
+                name.clone()
+            } else {
+                return Lookup::Missing;
+            };
+            actual.push(&name);
+            on_disk.push(name);
+        }
+        if wrong_case {
+            Lookup::WrongCase(actual, on_disk.join("/"))
+        } else {
+            Lookup::Exact(actual)
+        }
+    }
+
+    /// Search each place DreamMaker would look for `relative`.
+    fn lookup(&mut self, location: Location, relative: &str) -> Lookup {
+        let mut bases = Vec::new();
+        if !location.is_builtins() && Some(location.file) != self.env_file {
+            let source = self.env_dir.join(self.context.file_path(location.file));
+            if let Some(parent) = source.parent() {
+                bases.push(parent.to_owned());
+            }
+        }
+        bases.push(self.env_dir.to_owned());
+        for dir in self.file_dirs.iter() {
+            bases.push(self.env_dir.join(dir));
+        }
+
+        let mut result = Lookup::Missing;
+        for base in bases {
+            match self.lookup_in(&base, relative) {
+                Lookup::Exact(path) => return Lookup::Exact(path),
+                found @ Lookup::WrongCase(..) if matches!(result, Lookup::Missing) => {
+                    result = found
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    fn check_resource(&mut self, location: Location, resource: &str) {
+        let relative = resource.replace('\\', "/");
+        match self.lookup(location, &relative) {
+            Lookup::Exact(path) => {
+                self.referenced.insert(path);
+            }
+            Lookup::WrongCase(path, on_disk) => {
+                self.referenced.insert(path);
+                error(
+                    location,
+                    format!(
+                        "resource '{}' does not match the case of the file on disk: '{}'",
+                        resource, on_disk
+                    ),
+                )
+                .set_severity(Severity::Warning)
+                .with_errortype("resource_case_mismatch")
+                .register(self.context);
+            }
+            Lookup::Missing => {
+                error(location, format!("resource not found: '{}'", resource))
+                    .with_errortype("missing_resource")
+                    .register(self.context);
+            }
+        }
+    }
+
+    /// Strings can name assets too, as with `file("...")` or `browse_rsc()`.
+    fn note_string(&mut self, location: Location, text: &str) {
+        if !text.contains('.') || text.contains('\n') {
+            return;
+        }
+        match self.lookup(location, &text.replace('\\', "/")) {
+            Lookup::Exact(path) | Lookup::WrongCase(path, _) => {
+                self.referenced.insert(path);
+            }
+            Lookup::Missing => {}
+        }
+    }
+
+    fn visit_constant(&mut self, location: Location, constant: &Constant) {
+        match constant {
+            Constant::Resource(path) => self.check_resource(location, path),
+            Constant::String(text) => self.note_string(location, text),
+            Constant::List(args) | Constant::Call(_, args) => {
+                for (key, value) in args.iter() {
+                    self.visit_constant(location, key);
+                    if let Some(value) = value {
+                        self.visit_constant(location, value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_unreferenced(&mut self) {
+        // Walking the whole tree is only worthwhile if the report is enabled.
+        let probe = error(Location::default(), "").with_errortype("unused_resource");
+        if self
+            .context
+            .config()
+            .set_configured_severity(probe)
+            .is_none()
+        {
+            return;
+        }
+
+        let location = Location {
+            file: self.env_file.unwrap_or(Location::default().file),
+            line: 1,
+            column: 1,
+        };
+        let mut assets = Vec::new();
+        collect_assets(self.env_dir, &mut assets);
+        assets.sort();
+        for asset in assets {
+            if !self.referenced.contains(&asset) {
+                let relative = asset.strip_prefix(self.env_dir).unwrap_or(&asset);
+                error(
+                    location,
+                    format!(
+                        "resource is never referenced: '{}'",
+                        relative.display().to_string().replace('\\', "/")
+                    ),
+                )
+                .set_severity(Severity::Hint)
+                .with_errortype("unused_resource")
+                .register(self.context);
+            }
+        }
+    }
+}
+
+impl Walker for ResourceChecker<'_> {
+    fn term(&mut self, location: Location, term: &Term) {
+        match term {
+            Term::Resource(path) => self.check_resource(location, path),
+            Term::String(text) => self.note_string(location, text),
+            _ => {}
+        }
+    }
+}
+
+/// Recursively find asset files, skipping hidden directories like `.git`.
+fn collect_assets(dir: &Path, assets: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = dir.join(name);
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => collect_assets(&path, assets),
+            Ok(_) => {
+                let is_asset = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| {
+                        ASSET_EXTENSIONS
+                            .iter()
+                            .any(|each| each.eq_ignore_ascii_case(ext))
+                    });
+                if is_asset {
+                    assets.push(path);
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
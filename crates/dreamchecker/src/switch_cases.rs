@@ -0,0 +1,269 @@
+use dreammaker as dm;
+
+use dm::ast::*;
+use dm::constants::Constant;
+use dm::preprocessor::{LiteralMacro, LiteralMacros};
+use dm::{Context, DMError, Location, Severity};
+
+/// What kind of value a switch is known to be on. Only the switch expression
+/// itself is looked at, as what a local var holds may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SwitchInput {
+    Number,
+    Text,
+}
+
+impl SwitchInput {
+    fn describe(self) -> &'static str {
+        match self {
+            SwitchInput::Number => "a number",
+            SwitchInput::Text => "text",
+        }
+    }
+}
+
+/// A case value which could be evaluated.
+struct SeenCase<'a> {
+    location: Location,
+    value: CaseValue,
+    name: Option<&'a LiteralMacro>,
+}
+
+enum CaseValue {
+    Exact(Constant),
+    Range(f32, f32),
+}
+
+impl SeenCase<'_> {
+    fn contains(&self, value: f32) -> bool {
+        match self.value {
+            CaseValue::Exact(Constant::Float(f)) => f == value,
+            CaseValue::Exact(_) => false,
+            CaseValue::Range(min, max) => min <= value && value <= max,
+        }
+    }
+
+    fn overlaps(&self, min: f32, max: f32) -> bool {
+        match self.value {
+            CaseValue::Exact(Constant::Float(f)) => min <= f && f <= max,
+            CaseValue::Exact(_) => false,
+            CaseValue::Range(start, end) => start <= max && end >= min,
+        }
+    }
+}
+
+impl std::fmt::Display for SeenCase<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (&self.value, self.name) {
+            (CaseValue::Exact(value), Some(name)) => write!(f, "{} ({})", name.name, value),
+            (CaseValue::Exact(value), None) => write!(f, "{}", value),
+            (CaseValue::Range(min, max), _) => write!(f, "{} to {}", min, max),
+        }
+    }
+}
+
+/**
+ * Checks the cases of a `switch` for values which repeat or overlap an earlier
+ * case, or which can never match what is switched on.
+ * A switch over the members of an enumeration of `#define`s which neither
+ * handles every member nor has an `else` is also reported.
+ */
+pub fn check_switch_cases(
+    input: &Expression,
+    cases: &SwitchCases,
+    default: &Option<Block>,
+    location: Location,
+    macros: &LiteralMacros,
+    context: &Context,
+) {
+    let input_kind = input_kind(input);
+    let mut seen: Vec<SeenCase> = Vec::new();
+    let mut all_macros = true;
+
+    for (case_block, _) in cases.iter() {
+        for case in case_block.elem.iter() {
+            let location = match case {
+                Case::Exact(expr) | Case::Range(expr, _) => {
+                    expression_location(expr).unwrap_or(case_block.location)
+                }
+            };
+            let this = match case {
+                Case::Exact(expr) => {
+                    let name = match expr {
+                        Expression::Base { term, follow } if follow.is_empty() => {
+                            macros.at(term.location)
+                        }
+                        _ => None,
+                    };
+                    all_macros &= name.is_some();
+                    let Ok(value) = expr.to_owned().simple_evaluate(location) else {
+                        continue;
+                    };
+                    SeenCase {
+                        location,
+                        value: CaseValue::Exact(value),
+                        name,
+                    }
+                }
+                Case::Range(min, max) => {
+                    all_macros = false;
+                    let Some(min) = evaluate_float(min, location) else {
+                        continue;
+                    };
+                    let Some(max) = evaluate_float(max, location) else {
+                        continue;
+                    };
+                    SeenCase {
+                        location,
+                        value: CaseValue::Range(min, max),
+                        name: None,
+                    }
+                }
+            };
+
+            if let Some(input_kind) = input_kind
+                && !can_match(input_kind, &this.value)
+            {
+                warning(
+                    location,
+                    format!(
+                        "case {} can never match: the switch is on {}",
+                        this,
+                        input_kind.describe()
+                    ),
+                    "impossible_case",
+                )
+                .register(context);
+                continue;
+            }
+
+            let earlier = match &this.value {
+                CaseValue::Exact(Constant::Float(f)) => seen.iter().find(|each| each.contains(*f)),
+                CaseValue::Exact(value) => seen
+                    .iter()
+                    .find(|each| matches!(&each.value, CaseValue::Exact(other) if other == value)),
+                CaseValue::Range(min, max) => seen.iter().find(|each| each.overlaps(*min, *max)),
+            };
+            if let Some(earlier) = earlier {
+                let (desc, errortype) = match (&this.value, &earlier.value) {
+                    (CaseValue::Exact(_), CaseValue::Exact(_)) => {
+                        (format!("duplicate case {}", this), "duplicate_case")
+                    }
+                    _ => (
+                        format!("case {} overlaps earlier case {}", this, earlier),
+                        "overlapping_case",
+                    ),
+                };
+                warning(location, desc, errortype)
+                    .with_note(
+                        earlier.location,
+                        format!("{} is handled here first", earlier),
+                    )
+                    .register(context);
+            }
+            seen.push(this);
+        }
+    }
+
+    if default.is_none() && all_macros {
+        check_missing_else(&seen, location, macros, context);
+    }
+}
+
+/// Checks that a switch whose cases are all members of one enumeration of
+/// `#define`s handles every member.
+fn check_missing_else(
+    seen: &[SeenCase],
+    location: Location,
+    macros: &LiteralMacros,
+    context: &Context,
+) {
+    let Some(first) = seen.first().and_then(|each| each.name) else {
+        return;
+    };
+    let family = macros.family(first);
+    if family.len() < 2 {
+        return;
+    }
+    if !seen.iter().all(|each| {
+        each.name
+            .is_some_and(|name| family.iter().any(|member| member.name == name.name))
+    }) {
+        return;
+    }
+
+    let missing: Vec<&str> = family
+        .iter()
+        .filter(|member| {
+            !seen.iter().any(
+                |each| matches!(&each.value, CaseValue::Exact(value) if *value == member.value),
+            )
+        })
+        .map(|member| member.name.as_str())
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+
+    let prefix = &first.name[..=first.name.rfind('_').unwrap_or(0)];
+    warning(
+        location,
+        format!(
+            "switch over {}* has no else, and doesn't handle {}",
+            prefix,
+            missing.join(", ")
+        ),
+        "missing_switch_else",
+    )
+    .register(context);
+}
+
+fn warning(location: Location, desc: String, errortype: &'static str) -> DMError {
+    DMError::new(location, desc)
+        .with_component(dm::Component::DreamChecker)
+        .set_severity(Severity::Warning)
+        .with_errortype(errortype)
+}
+
+fn expression_location(expr: &Expression) -> Option<Location> {
+    match expr {
+        Expression::Base { term, .. } => Some(term.location),
+        Expression::BinaryOp { lhs, .. } => expression_location(lhs),
+        _ => None,
+    }
+}
+
+fn evaluate_float(expr: &Expression, location: Location) -> Option<f32> {
+    expr.to_owned().simple_evaluate(location).ok()?.to_float()
+}
+
+fn can_match(input: SwitchInput, value: &CaseValue) -> bool {
+    !matches!(
+        (input, value),
+        (SwitchInput::Number, CaseValue::Exact(Constant::String(_)))
+            | (SwitchInput::Text, CaseValue::Exact(Constant::Float(_)))
+            | (SwitchInput::Text, CaseValue::Range(..))
+    )
+}
+
+/// What kind of value a builtin call or arithmetic always gives, if known.
+fn input_kind(input: &Expression) -> Option<SwitchInput> {
+    match input {
+        Expression::Base { term, follow } if follow.is_empty() => match &term.elem {
+            Term::Call(name, _) => match name.as_str() {
+                "rand" | "round" | "floor" | "ceil" | "trunc" | "fract" | "abs" | "sqrt"
+                | "text2num" | "length" | "get_dist" | "findtext" | "findtextEx" | "text2ascii"
+                | "prob" => Some(SwitchInput::Number),
+                "num2text" | "lowertext" | "uppertext" | "copytext" | "ckey" | "ckeyEx"
+                | "replacetext" | "replacetextEx" | "jointext" | "text" => Some(SwitchInput::Text),
+                _ => None,
+            },
+            _ => None,
+        },
+        Expression::BinaryOp {
+            op: BinaryOp::Mod | BinaryOp::FloatMod | BinaryOp::Pow,
+            ..
+        } => Some(SwitchInput::Number),
+        _ => None,
+    }
+}
@@ -1,6 +1,7 @@
 use dreammaker as dm;
 
 use dm::Context;
+use dm::objtree::ObjectTree;
 use std::borrow::Cow;
 
 use crate::run_inner;
@@ -8,27 +9,94 @@ use crate::run_inner;
 pub const NO_ERRORS: &[(u32, u16, &str)] = &[];
 
 pub fn parse_a_file_for_test<S: Into<Cow<'static, str>>>(buffer: S) -> Context {
-    let context = Context::default();
+    parse_and_check(Context::default(), buffer, false)
+}
 
-    let pp = dm::preprocessor::Preprocessor::from_buffer(
-        &context,
-        "unit_tests.rs".into(),
-        buffer.into(),
-    );
+/// Like `parse_a_file_for_test`, but analyze proc bodies in parallel.
+pub fn parse_a_file_for_test_parallel<S: Into<Cow<'static, str>>>(buffer: S) -> Context {
+    parse_and_check(Context::default(), buffer, true)
+}
 
-    let indents = dm::indents::IndentProcessor::new(&context, pp);
+fn parse_and_check<S: Into<Cow<'static, str>>>(
+    context: Context,
+    buffer: S,
+    parallel: bool,
+) -> Context {
+    let tree = parse_tree(&context, buffer);
+    run_inner(&context, &tree, false, parallel);
+    context.check_unused_suppressions();
+    context
+}
 
-    let mut parser = dm::parser::Parser::new(&context, indents);
-    parser.enable_procs();
-    let tree = parser.parse_object_tree();
+fn parse_tree<S: Into<Cow<'static, str>>>(context: &Context, buffer: S) -> ObjectTree {
+    let pp =
+        dm::preprocessor::Preprocessor::from_buffer(context, "unit_tests.rs".into(), buffer.into());
 
-    run_inner(&context, &tree, false);
+    let indents = dm::indents::IndentProcessor::new(context, pp);
 
-    context
+    let mut parser = dm::parser::Parser::new(context, indents);
+    parser.enable_procs();
+    parser.parse_object_tree()
 }
 
 pub fn check_errors_match<S: Into<Cow<'static, str>>>(buffer: S, errorlist: &[(u32, u16, &str)]) {
-    let context = parse_a_file_for_test(buffer);
+    compare_errors(&parse_a_file_for_test(buffer), errorlist);
+}
+
+/// Like `check_errors_match`, but with a `SpacemanDMM.toml` configuration.
+pub fn check_errors_match_with_config<S: Into<Cow<'static, str>>>(
+    buffer: S,
+    config_toml: &str,
+    errorlist: &[(u32, u16, &str)],
+) {
+    let config = dm::config::Config::from_toml(config_toml).expect("invalid test config");
+    compare_errors(
+        &parse_and_check(Context::with_config(config), buffer, false),
+        errorlist,
+    );
+}
+
+/// Like `check_errors_match_with_config`, but also check icon states against
+/// the `.dmi` files in `icons_root`.
+pub fn check_icon_errors_match<S: Into<Cow<'static, str>>>(
+    buffer: S,
+    config_toml: &str,
+    icons_root: &std::path::Path,
+    errorlist: &[(u32, u16, &str)],
+) {
+    let config = dm::config::Config::from_toml(config_toml).expect("invalid test config");
+    let context = Context::with_config(config);
+    let tree = parse_tree(&context, buffer);
+    run_inner(&context, &tree, false, false);
+    crate::icons::check_icons(&context, &tree, icons_root);
+    compare_errors(&context, errorlist);
+}
+
+/// Like `check_errors_match_with_config`, but also check resources against the
+/// files in `root`, as if the code were `root/test.dme`.
+pub fn check_resource_errors_match<S: Into<Cow<'static, str>>>(
+    buffer: S,
+    config_toml: &str,
+    root: &std::path::Path,
+    errorlist: &[(u32, u16, &str)],
+) {
+    let config = dm::config::Config::from_toml(config_toml).expect("invalid test config");
+    let context = Context::with_config(config);
+    let environment = root.join("test.dme");
+    let mut pp =
+        dm::preprocessor::Preprocessor::from_buffer(&context, environment.clone(), buffer.into());
+    let tree = {
+        let indents = dm::indents::IndentProcessor::new(&context, &mut pp);
+        let mut parser = dm::parser::Parser::new(&context, indents);
+        parser.enable_procs();
+        parser.parse_object_tree()
+    };
+    let file_dirs = crate::resources::file_dirs(&pp.finalize());
+    crate::resources::check_resources(&context, &tree, &environment, &file_dirs);
+    compare_errors(&context, errorlist);
+}
+
+fn compare_errors(context: &Context, errorlist: &[(u32, u16, &str)]) {
     let errors = context.errors();
     let mut iter = errors.iter();
     for (line, column, desc) in errorlist {
@@ -73,6 +73,15 @@ impl<'o> TypeExpr<'o> {
         .visit_expression(location, expression)
     }
 
+    /// Whether this type expression may evaluate to `null`.
+    pub fn is_nullable(&self) -> bool {
+        match self {
+            TypeExpr::Static(st) => !st.is_truthy(),
+            TypeExpr::Condition { if_, else_, .. } => if_.is_nullable() || else_.is_nullable(),
+            TypeExpr::ParamTypepath { .. } | TypeExpr::ParamStaticType { .. } => false,
+        }
+    }
+
     pub fn evaluate(
         &self,
         location: Location,
@@ -0,0 +1,233 @@
+//! Walking proc bodies, for checks which only need to see each statement and
+//! term rather than analyze control flow.
+
+use dm::Location;
+use dm::ast::*;
+use dreammaker as dm;
+
+/// Callbacks for `walk_block`. Each is called before any nested contents are
+/// walked.
+pub trait Walker {
+    fn statement(&mut self, location: Location, statement: &Statement) {
+        let _ = (location, statement);
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        let _ = expr;
+    }
+
+    fn term(&mut self, location: Location, term: &Term) {
+        let _ = (location, term);
+    }
+}
+
+pub fn walk_block<W: Walker + ?Sized>(walker: &mut W, block: &[Spanned<Statement>]) {
+    for stmt in block.iter() {
+        walk_statement(walker, stmt.location, &stmt.elem);
+    }
+}
+
+fn walk_statement<W: Walker + ?Sized>(walker: &mut W, location: Location, statement: &Statement) {
+    walker.statement(location, statement);
+    match statement {
+        Statement::Expr(expr)
+        | Statement::Return(Some(expr))
+        | Statement::Throw(expr)
+        | Statement::Del(expr)
+        | Statement::Crash(Some(expr))
+        | Statement::Setting { value: expr, .. } => walk_expression(walker, expr),
+        Statement::Return(None)
+        | Statement::Crash(None)
+        | Statement::Continue(_)
+        | Statement::Break(_)
+        | Statement::Goto(_) => {}
+        Statement::While { condition, block } => {
+            walk_expression(walker, condition);
+            walk_block(walker, block);
+        }
+        Statement::DoWhile { block, condition } => {
+            walk_block(walker, block);
+            walk_expression(walker, &condition.elem);
+        }
+        Statement::If { arms, else_arm } => {
+            for (condition, block) in arms.iter() {
+                walk_expression(walker, &condition.elem);
+                walk_block(walker, block);
+            }
+            if let Some(else_arm) = else_arm {
+                walk_block(walker, else_arm);
+            }
+        }
+        Statement::ForInfinite { block } | Statement::Label { block, .. } => {
+            walk_block(walker, block)
+        }
+        Statement::ForLoop {
+            init,
+            test,
+            inc,
+            block,
+        } => {
+            if let Some(init) = init {
+                walk_statement(walker, location, init);
+            }
+            if let Some(test) = test {
+                walk_expression(walker, test);
+            }
+            if let Some(inc) = inc {
+                walk_statement(walker, location, inc);
+            }
+            walk_block(walker, block);
+        }
+        Statement::ForList(for_list) => {
+            if let Some(in_list) = for_list.in_list.as_ref() {
+                walk_expression(walker, in_list);
+            }
+            walk_block(walker, &for_list.block);
+        }
+        Statement::ForKeyValue(for_key_value) => {
+            if let Some(in_list) = for_key_value.in_list.as_ref() {
+                walk_expression(walker, in_list);
+            }
+            walk_block(walker, &for_key_value.block);
+        }
+        Statement::ForRange(for_range) => {
+            walk_expression(walker, &for_range.start);
+            walk_expression(walker, &for_range.end);
+            if let Some(step) = for_range.step.as_ref() {
+                walk_expression(walker, step);
+            }
+            walk_block(walker, &for_range.block);
+        }
+        Statement::Var(var) => {
+            if let Some(value) = var.value.as_ref() {
+                walk_expression(walker, value);
+            }
+        }
+        Statement::Vars(vars) => {
+            for var in vars.iter() {
+                if let Some(value) = var.value.as_ref() {
+                    walk_expression(walker, value);
+                }
+            }
+        }
+        Statement::Spawn { delay, block } => {
+            if let Some(delay) = delay {
+                walk_expression(walker, delay);
+            }
+            walk_block(walker, block);
+        }
+        Statement::Switch {
+            input,
+            cases,
+            default,
+        } => {
+            walk_expression(walker, input);
+            for (case, block) in cases.iter() {
+                for case_part in case.elem.iter() {
+                    match case_part {
+                        Case::Exact(expr) => walk_expression(walker, expr),
+                        Case::Range(start, end) => {
+                            walk_expression(walker, start);
+                            walk_expression(walker, end);
+                        }
+                    }
+                }
+                walk_block(walker, block);
+            }
+            if let Some(default) = default {
+                walk_block(walker, default);
+            }
+        }
+        Statement::TryCatch {
+            try_block,
+            catch_block,
+            ..
+        } => {
+            walk_block(walker, try_block);
+            walk_block(walker, catch_block);
+        }
+    }
+}
+
+pub fn walk_expression<W: Walker + ?Sized>(walker: &mut W, expr: &Expression) {
+    walker.expression(expr);
+    match expr {
+        Expression::Base { term, follow } => {
+            walk_term(walker, term.location, &term.elem);
+            for each in follow.iter() {
+                match &each.elem {
+                    Follow::Index(_, expr) => walk_expression(walker, expr),
+                    Follow::Call(_, _, args) => walk_expressions(walker, args),
+                    _ => {}
+                }
+            }
+        }
+        Expression::BinaryOp { lhs, rhs, .. } | Expression::AssignOp { lhs, rhs, .. } => {
+            walk_expression(walker, lhs);
+            walk_expression(walker, rhs);
+        }
+        Expression::TernaryOp { cond, if_, else_ } => {
+            walk_expression(walker, cond);
+            walk_expression(walker, if_);
+            walk_expression(walker, else_);
+        }
+    }
+}
+
+fn walk_expressions<W: Walker + ?Sized>(walker: &mut W, exprs: &[Expression]) {
+    for expr in exprs.iter() {
+        walk_expression(walker, expr);
+    }
+}
+
+fn walk_term<W: Walker + ?Sized>(walker: &mut W, location: Location, term: &Term) {
+    walker.term(location, term);
+    match term {
+        Term::InterpString(_, parts) => {
+            for expr in parts.iter().filter_map(|(expr, _)| expr.as_ref()) {
+                walk_expression(walker, expr);
+            }
+        }
+        Term::Expr(expr) => walk_expression(walker, expr),
+        Term::Call(_, args)
+        | Term::SelfCall(args)
+        | Term::ParentCall(args)
+        | Term::GlobalCall(_, args)
+        | Term::List(args) => walk_expressions(walker, args),
+        Term::NewImplicit { args }
+        | Term::NewPrefab { args, .. }
+        | Term::NewMiniExpr { args, .. } => {
+            if let Some(args) = args {
+                walk_expressions(walker, args);
+            }
+        }
+        Term::Input { args, in_list, .. } | Term::Locate { args, in_list } => {
+            walk_expressions(walker, args);
+            if let Some(in_list) = in_list {
+                walk_expression(walker, in_list);
+            }
+        }
+        Term::Pick(pick) => {
+            for (weight, value) in pick.iter() {
+                if let Some(weight) = weight {
+                    walk_expression(walker, weight);
+                }
+                walk_expression(walker, value);
+            }
+        }
+        Term::DynamicCall(lhs, rhs) => {
+            walk_expressions(walker, lhs);
+            walk_expressions(walker, rhs);
+        }
+        Term::ExternalCall {
+            library_name,
+            function_name,
+            args,
+        } => {
+            walk_expression(walker, library_name);
+            walk_expression(walker, function_name);
+            walk_expressions(walker, args);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,87 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const CONSTANT_FOLDING_ERRORS: &[(u32, u16, &str)] = &[
+    (2, 15, "division by constant zero"),
+    (
+        3,
+        14,
+        "bitwise operand 16777216 exceeds 24 bits and is truncated",
+    ),
+    (4, 17, "shift by negative count -1"),
+    (5, 13, "1 << 24 overflows 24 bits and is truncated"),
+    (
+        6,
+        13,
+        "precision loss of integer arithmetic: 16777216 + 1 is 16777216, not 16777217",
+    ),
+    (10, 5, "modulo by constant zero"),
+    (
+        11,
+        5,
+        "bitwise operand 33554432 exceeds 24 bits and is truncated",
+    ),
+    (12, 5, "shift by negative count -2"),
+    (
+        13,
+        13,
+        "bitwise operand 16777216 exceeds 24 bits and is truncated",
+    ),
+    (
+        17,
+        5,
+        "precision loss of integer arithmetic: 4097 * 4097 is 16785408, not 16785409",
+    ),
+];
+
+#[test]
+fn constant_folding() {
+    let code = r##"
+/obj
+    var/ratio = 10 / 0
+    var/mask = 16777216 | 1
+    var/shifted = 1 << -1
+    var/big = 1 << 24
+    var/sum = 16777216 + 1
+    var/fine = ((1 << 23) | 255) + 16777215 * 1
+
+/proc/test(x)
+    var/a = x % 0
+    var/b = x & 33554432
+    var/c = x >> -2
+    var/d = ~16777216
+    var/e = 1 / 2 + 0 / 1
+    var/f = 4096 * 4097
+    var/g = 1.5 * 20000000
+    var/h = 4097 * 4097
+    return a + b + c + d + e + f + g + h
+"##
+    .trim();
+    check_errors_match(code, CONSTANT_FOLDING_ERRORS);
+}
+
+#[test]
+fn constant_folding_reassigned_locals() {
+    let code = r##"
+/proc/average(list/L)
+    var/total = 0
+    var/count = 0
+    for(var/x in L)
+        total += x
+        count++
+    if(!count)
+        return 0
+    return total / count
+
+/proc/scale(x)
+    var/n = 0
+    n = x
+    var/m = 0
+    if(x)
+        m = x
+    return 10 / n + 10 % m + ~n
+"##
+    .trim();
+    check_errors_match(code, &[]);
+}
@@ -0,0 +1,171 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const FORBID_CALL_ERRORS: &[(u32, u16, &str)] = &[
+    (5, 5, "call to sleep is forbidden: use stoplag() instead"),
+    (7, 6, "call to /mob/proc/gib is forbidden"),
+    (8, 5, "call to del is forbidden"),
+];
+
+#[test]
+fn forbid_call() {
+    let code = r##"
+/mob/proc/gib()
+    gib()
+
+/mob/proc/test(mob/M)
+    sleep(1)
+    world.log << "ok"
+    M.gib()
+    del(M)
+
+/obj/proc/gib()
+
+/obj/proc/test()
+    gib()
+    sleep(1)
+"##
+    .trim();
+    let config = r#"
+[[lints]]
+errortype = "no_sleep"
+forbid_call = "sleep"
+message = "use stoplag() instead"
+exclude_types = ["/obj"]
+
+[[lints]]
+errortype = "no_gib"
+forbid_call = "/mob/proc/gib"
+
+[[lints]]
+errortype = "no_del"
+severity = "error"
+forbid_call = "del"
+"#;
+    check_errors_match_with_config(code, config, FORBID_CALL_ERRORS);
+}
+
+pub const FORBID_VAR_ERRORS: &[(u32, u16, &str)] = &[
+    (6, 5, "writing /atom/var/density is forbidden"),
+    (7, 6, "writing /atom/var/density is forbidden"),
+    (8, 15, "reading /atom/var/density is forbidden"),
+    (9, 8, "reading /atom/var/density is forbidden"),
+    (9, 8, "writing /atom/var/density is forbidden"),
+    (11, 22, "reading /atom/var/density is forbidden"),
+];
+
+#[test]
+fn forbid_var() {
+    let code = r##"
+/datum/thing
+    var/density
+
+/obj/proc/test(obj/O, datum/thing/T)
+    var/value = 1
+    density = 0
+    O.density = value
+    world << O.density
+    src.density ^= 1
+    T.density = 1
+    return isnull(src.density)
+"##
+    .trim();
+    let config = r#"
+[[lints]]
+errortype = "density_read"
+forbid_read = "/atom/var/density"
+types = ["/obj"]
+
+[[lints]]
+errortype = "density_write"
+forbid_write = "/atom/var/density"
+"#;
+    check_errors_match_with_config(code, config, FORBID_VAR_ERRORS);
+}
+
+pub const FORBID_BUILTIN_ERRORS: &[(u32, u16, &str)] = &[
+    (2, 18, "reading usr is forbidden"),
+    (11, 18, "reading usr is forbidden"),
+    (12, 5, "writing usr is forbidden"),
+    (13, 18, "reading src is forbidden"),
+    (6, 5, "reading /atom/var/density is forbidden"),
+    (6, 5, "writing /atom/var/density is forbidden"),
+    (7, 10, "reading /atom/var/density is forbidden"),
+    (7, 10, "writing /atom/var/density is forbidden"),
+];
+
+#[test]
+fn forbid_builtin_and_step() {
+    let code = r##"
+/proc/test()
+    world.log << usr
+
+/obj/verb/poke()
+    set src in view()
+    density++
+    --src.density
+    world.log << src
+
+/mob/verb/look()
+    world.log << usr
+    usr = null
+    world.log << src
+
+/mob/proc/helper()
+    world.log << src
+"##
+    .trim();
+    let config = r#"
+[[lints]]
+errortype = "no_usr"
+forbid_read = "usr"
+forbid_write = "usr"
+
+[[lints]]
+errortype = "density_read"
+forbid_read = "/atom/var/density"
+
+[[lints]]
+errortype = "density_write"
+forbid_write = "/atom/var/density"
+
+[[lints]]
+errortype = "no_src_in_verbs"
+forbid_read = "src"
+verbs_only = true
+types = ["/mob"]
+"#;
+    check_errors_match_with_config(code, config, FORBID_BUILTIN_ERRORS);
+}
+
+pub const PARENT_CALL_ERRORS: &[(u32, u16, &str)] =
+    &[(7, 22, "/obj/thing/proc/Initialize must call ..()")];
+
+#[test]
+fn require_parent_call() {
+    let code = r##"
+/atom/proc/Initialize()
+    return
+
+/obj/Initialize()
+    . = ..()
+
+/obj/thing/Initialize()
+    return
+
+/turf/Initialize()
+    return
+
+/obj/other/Destroy()
+    return
+"##
+    .trim();
+    let config = r#"
+[[lints]]
+errortype = "initialize_parent"
+require_parent_call = "Initialize"
+types = ["/obj"]
+"#;
+    check_errors_match_with_config(code, config, PARENT_CALL_ERRORS);
+}
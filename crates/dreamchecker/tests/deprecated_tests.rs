@@ -0,0 +1,62 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const DEPRECATED_PROC_ERRORS: &[(u32, u16, &str)] = &[
+    (12, 6, "/datum/proc/old is deprecated: use new() instead"),
+    (13, 6, "/datum/proc/old is deprecated: use new() instead"),
+    (14, 5, "/proc/legacy is deprecated"),
+    (
+        7,
+        9,
+        "/obj/proc/old overrides deprecated proc /datum/proc/old: use new() instead",
+    ),
+];
+
+#[test]
+fn deprecated_proc() {
+    let code = r##"
+/datum/proc/old()
+    set SpacemanDMM_deprecated = "use new() instead"
+    return old()
+
+/datum/proc/new()
+
+/obj/old()
+    return ..()
+
+/proc/test(datum/D, obj/O)
+    D.new()
+    D.old()
+    O.old()
+    legacy()
+
+/proc/legacy()
+    set SpacemanDMM_deprecated = TRUE
+"##
+    .trim();
+    check_errors_match(code, DEPRECATED_PROC_ERRORS);
+}
+
+pub const DEPRECATED_VAR_ERRORS: &[(u32, u16, &str)] = &[
+    (5, 9, "/datum/thing/child overrides deprecated var \"old\""),
+    (8, 14, "var \"old\" on /datum/thing is deprecated"),
+    (9, 6, "var \"old\" on /datum/thing is deprecated"),
+];
+
+#[test]
+fn deprecated_var() {
+    let code = r##"
+/datum/thing
+    var/SpacemanDMM_deprecated/old = 1
+
+/datum/thing/child
+    old = 2
+
+/datum/thing/proc/test(datum/thing/T)
+    world << old
+    T.old = 3
+"##
+    .trim();
+    check_errors_match(code, DEPRECATED_VAR_ERRORS);
+}
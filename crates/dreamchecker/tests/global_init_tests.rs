@@ -0,0 +1,51 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const GLOBAL_INIT_ORDER_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        15,
+        18,
+        "global var chicken is initialized from egg, which depends on it",
+    ),
+    (
+        17,
+        14,
+        "global var self_ref reads itself while it is initialized",
+    ),
+    (
+        4,
+        28,
+        "global var early reads late before it is initialized",
+    ),
+    (
+        12,
+        34,
+        "global var also_early reads still_later before it is initialized",
+    ),
+];
+
+#[test]
+fn global_init_order() {
+    let code = r##"
+/proc/make_list(x)
+    return list(x)
+
+var/list/early = make_list(late)
+var/list/late = list(1, 2)
+var/list/fine = make_list(late)
+var/list/uses_const = make_list(LIMIT)
+var/const/LIMIT = 10
+var/number = global.later_number + 1
+var/later_number = 5
+var/datum/thing = new
+var/list/also_early = list(global.still_later)
+var/datum/still_later = new
+
+var/list/chicken = list(egg)
+var/list/egg = list(chicken)
+var/self_ref = make_list(self_ref)
+"##
+    .trim();
+    check_errors_match(code, GLOBAL_INIT_ORDER_ERRORS);
+}
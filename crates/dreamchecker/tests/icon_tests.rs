@@ -0,0 +1,122 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+use std::path::PathBuf;
+
+const METADATA: &str = r#"# BEGIN DMI
+version = 4.0
+	width = 32
+	height = 32
+state = "closed"
+	dirs = 1
+	frames = 1
+state = "open"
+	dirs = 4
+	frames = 1
+state = "broken_1"
+	dirs = 1
+	frames = 1
+state = "spare"
+	dirs = 1
+	frames = 1
+# END DMI
+"#;
+
+/// Write `icons/door.dmi` into a fresh directory named after the test.
+fn icons_root(test: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("dreamchecker-icon-tests-{}", test));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("icons")).unwrap();
+
+    let frames = 7;
+    let file = std::fs::File::create(root.join("icons/door.dmi")).unwrap();
+    let mut encoder = png::Encoder::new(file, 32 * frames, 32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .add_ztxt_chunk("Description".to_owned(), METADATA.to_owned())
+        .unwrap();
+    let mut writer = encoder.write_header().unwrap();
+    writer
+        .write_image_data(&vec![0; (32 * frames * 32 * 4) as usize])
+        .unwrap();
+    root
+}
+
+pub const ICON_STATE_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        16,
+        5,
+        "icon_state \"opne\" does not exist in 'icons/door.dmi'",
+    ),
+    (
+        6,
+        16,
+        "icon_state \"brokne\" does not exist in 'icons/door.dmi'",
+    ),
+    (
+        12,
+        16,
+        "dir 5 cannot be shown by icon_state \"open\" in 'icons/door.dmi', which has 4 direction(s)",
+    ),
+];
+
+#[test]
+fn icon_states() {
+    let code = r##"
+/obj/door
+    icon = 'icons/door.dmi'
+    icon_state = "closed"
+
+/obj/door/broken
+    icon_state = "brokne"
+
+/obj/door/sideways
+    dir = 4
+
+/obj/door/open
+    icon_state = "open"
+    dir = 5
+
+/obj/door/proc/open()
+    icon_state = "opne"
+    src.icon_state = "open"
+    icon_state = "broken_[rand(1, 2)]"
+
+/obj/panel
+    icon = 'icons/door.dmi'
+
+/obj/panel/lit
+    icon = 'icons/panel.dmi'
+
+/obj/panel/proc/light()
+    icon_state = "on"
+"##
+    .trim();
+    check_icon_errors_match(code, "", &icons_root("icon_states"), ICON_STATE_ERRORS);
+}
+
+pub const UNUSED_ERRORS: &[(u32, u16, &str)] = &[(
+    3,
+    16,
+    "'icons/door.dmi' has 1 icon_state(s) which nothing references: \"spare\"",
+)];
+
+#[test]
+fn unused_icon_states() {
+    let code = r##"
+/obj/door
+    icon = 'icons/door.dmi'
+    icon_state = "closed"
+
+/obj/door/proc/open()
+    icon_state = "open"
+    icon_state = "broken_[rand(1, 2)]"
+"##
+    .trim();
+    let config = r#"
+[diagnostics]
+icon_state_unused = "hint"
+"#;
+    check_icon_errors_match(code, config, &icons_root("unused"), UNUSED_ERRORS);
+}
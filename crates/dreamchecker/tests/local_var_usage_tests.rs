@@ -0,0 +1,143 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+const ENABLED: &str = r#"
+[diagnostics]
+unused_local_var = "warning"
+unused_parameter = "warning"
+use_before_assign = "warning"
+shadowed_var = "warning"
+"#;
+
+pub const UNUSED_ERRORS: &[(u32, u16, &str)] = &[
+    (2, 5, "unused local var: \"unused\""),
+    (4, 5, "unused local var: \"written\""),
+    (1, 16, "unused parameter \"b\" of /proc/test"),
+];
+
+#[test]
+fn unused() {
+    let code = r##"
+/proc/test(a, b, _c)
+    var/unused = 1
+    var/_ignored = 2
+    var/written
+    written = 3
+    var/read = a
+    for(var/i in 1 to 3)
+        read += i
+    return read
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, UNUSED_ERRORS);
+}
+
+#[test]
+fn unused_parameter_exemptions() {
+    let code = r##"
+/datum/proc/overridden(a)
+    return
+
+/datum/foo/overridden(a)
+    return
+
+/proc/forwards(a)
+    return args.len
+
+/datum/bar/New(a)
+    ..()
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, &[]);
+}
+
+pub const USE_BEFORE_ASSIGN_ERRORS: &[(u32, u16, &str)] = &[
+    (3, 14, "local var \"x\" is read before it is assigned"),
+    (13, 14, "local var \"z\" is read before it is assigned"),
+    (17, 14, "local var \"w\" is read before it is assigned"),
+];
+
+#[test]
+fn use_before_assign() {
+    let code = r##"
+/proc/test()
+    var/x
+    world << x
+    var/y
+    if(prob(50))
+        y = 1
+    else
+        y = 2
+    world << y
+    var/z
+    if(prob(50))
+        z = 1
+    world << z
+    var/w
+    for(var/i in 1 to 3)
+        w = i
+    world << w
+    var/v
+    switch(rand(1, 2))
+        if(1)
+            v = 1
+        else
+            return
+    world << v
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, USE_BEFORE_ASSIGN_ERRORS);
+}
+
+pub const USE_BEFORE_ASSIGN_BLOCK_ERRORS: &[(u32, u16, &str)] =
+    &[(18, 14, "local var \"u\" is read before it is assigned")];
+
+#[test]
+fn use_before_assign_do_while_try() {
+    let code = r##"
+/proc/test()
+    var/x
+    do
+        x = 1
+    while(prob(50))
+    world << x
+    var/y
+    try
+        y = 1
+    catch
+        return
+    world << y
+    var/u
+    try
+        u = 1
+    catch
+        world << "failed"
+    world << u
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, USE_BEFORE_ASSIGN_BLOCK_ERRORS);
+}
+
+pub const SHADOWED_ERRORS: &[(u32, u16, &str)] = &[
+    (5, 5, "local var \"name\" shadows var on /datum/foo"),
+    (8, 9, "local var \"x\" shadows an earlier local var"),
+];
+
+#[test]
+fn shadowed_var() {
+    let code = r##"
+/datum/foo
+    var/name
+
+/datum/foo/proc/test()
+    var/name = 1
+    var/x = name
+    if(x)
+        var/x = 2
+        return x
+    return x
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, SHADOWED_ERRORS);
+}
@@ -0,0 +1,91 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const LOOP_NEVER_YIELDS_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        10,
+        5,
+        "loop never yields: it has no break or return, and nothing in it sleeps",
+    ),
+    (
+        13,
+        5,
+        "loop never yields: it has no break or return, and nothing in it sleeps",
+    ),
+    (
+        15,
+        5,
+        "loop never yields: it has no break or return, and nothing in it sleeps",
+    ),
+    (
+        20,
+        5,
+        "loop never yields: it has no break or return, and nothing in it sleeps",
+    ),
+];
+
+#[test]
+fn loop_never_yields() {
+    let code = r##"
+/proc/stoplag()
+    sleep(1)
+
+/proc/background()
+    set waitfor = 0
+    stoplag()
+
+/proc/busy()
+    var/i = 0
+    while(TRUE)
+        i++
+        spawn(1) stoplag()
+    for()
+        background()
+    while(1)
+        for(var/j in 1 to 10)
+            if(j > i)
+                break
+        i++
+    do
+        i++
+    while(TRUE)
+
+/proc/fine(list/L)
+    while(TRUE)
+        stoplag()
+    for(;;)
+        if(L.len)
+            break
+    while(TRUE)
+        if(!L)
+            return
+    while(TRUE)
+        sleep(1)
+    while(TRUE)
+        wrapper()
+    while(L.len)
+        L.Cut(1, 2)
+
+/proc/wrapper()
+    stoplag()
+"##
+    .trim();
+    check_errors_match(code, LOOP_NEVER_YIELDS_ERRORS);
+}
+
+#[test]
+fn loop_override_sleeps() {
+    let code = r##"
+/datum/proc/process()
+
+/datum/thing/process()
+    sleep(1)
+
+/proc/tick_all(datum/D)
+    while(TRUE)
+        D.process()
+"##
+    .trim();
+    check_errors_match(code, NO_ERRORS);
+}
@@ -0,0 +1,71 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+#[test]
+fn istype_narrowing() {
+    let code = r##"
+#define isliving(A) (istype(A, /mob/living))
+
+/mob/living/proc/breathe()
+
+/proc/guard(atom/A)
+    if(!istype(A, /mob/living))
+        return
+    A.breathe()
+
+/proc/guard_or(A)
+    if(!A || !isliving(A))
+        CRASH("not living")
+    A.breathe()
+
+/proc/loop(list/things)
+    for(var/thing in things)
+        if(!isliving(thing))
+            continue
+        thing.breathe()
+
+/proc/chain(atom/A)
+    if(isliving(A) && A.breathe())
+        return
+    var/result = istype(A, /mob/living) ? A.breathe() : null
+    if(istype(A, /mob/living))
+        A.breathe()
+    else if(ismob(A))
+        return
+    else
+        return
+    A.breathe()
+"##
+    .trim();
+    check_errors_match(code, NO_ERRORS);
+}
+
+pub const NARROWING_MERGE_ERRORS: &[(u32, u16, &str)] = &[
+    (8, 6, "undefined proc: \"breathe\" on /mob"),
+    (10, 6, "undefined proc: \"breathe\" on /atom"),
+    (15, 6, "undefined proc: \"breathe\" on /obj"),
+];
+
+#[test]
+fn narrowing_merge() {
+    let code = r##"
+/mob/living/proc/breathe()
+
+/proc/partial(atom/A)
+    if(istype(A, /mob/living))
+        A.breathe()
+    else if(!ismob(A))
+        return
+    A.breathe()
+    A = new /obj
+    A.breathe()
+
+/proc/declared(obj/O)
+    if(istype(O, /mob/living))
+        O.breathe()
+    O.breathe()
+"##
+    .trim();
+    check_errors_match(code, NARROWING_MERGE_ERRORS);
+}
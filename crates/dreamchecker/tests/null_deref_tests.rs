@@ -0,0 +1,118 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+const ENABLED: &str = r#"
+[diagnostics]
+possible_null_deref = "warning"
+"#;
+
+pub const NULL_DEREF_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        6,
+        6,
+        "field access on a value which may be null from locate(): \"name\"",
+    ),
+    (
+        7,
+        6,
+        "proc call on a value which may be null from locate(): \"foo\"",
+    ),
+    (
+        11,
+        6,
+        "field access on a value which may be null from get_step(): \"name\"",
+    ),
+    (
+        15,
+        6,
+        "proc call on a value which may be null from a list index: \"foo\"",
+    ),
+    (
+        18,
+        6,
+        "proc call on a value which may be null from /mob/proc/find(): \"foo\"",
+    ),
+    (
+        22,
+        6,
+        "proc call on a value which may be null from locate(): \"foo\"",
+    ),
+];
+
+#[test]
+fn null_deref() {
+    let code = r##"
+/mob/proc/foo()
+/mob/proc/find()
+    set SpacemanDMM_return_type = /mob || null
+/proc/test_locate()
+    var/mob/M = locate(/mob)
+    M.name
+    M.foo()
+    M?.foo()
+/proc/test_get_step(atom/A)
+    var/turf/T = get_step(A, 1)
+    T.name
+/proc/test_index()
+    var/list/mob/L = list()
+    var/mob/M = L[1]
+    M.foo()
+/proc/test_return_type(mob/user)
+    var/mob/M = user.find()
+    M.foo()
+/proc/test_reassign()
+    var/mob/M = new
+    M = locate(/mob)
+    M.foo()
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, NULL_DEREF_ERRORS);
+}
+
+#[test]
+fn null_deref_guarded() {
+    let code = r##"
+/mob/var/ckey
+/mob/proc/foo()
+/proc/test_if()
+    var/mob/M = locate(/mob)
+    if(M)
+        M.foo()
+    if(istype(M) && M.ckey)
+        M.foo()
+    if(M != null)
+        M.foo()
+    M && M.foo()
+    M ? M.foo() : null
+/proc/test_early_return()
+    var/mob/M = locate(/mob)
+    if(!M)
+        return
+    M.foo()
+/proc/test_else()
+    var/mob/M = locate(/mob)
+    if(isnull(M))
+        M = new
+    M.foo()
+/proc/test_or()
+    var/mob/M = locate(/mob)
+    if(!M || !M.ckey)
+        return
+    M.foo()
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, NO_ERRORS);
+}
+
+#[test]
+fn null_deref_opt_in() {
+    let code = r##"
+/mob/proc/foo()
+/proc/test()
+    var/mob/M = locate(/mob)
+    M.foo()
+"##
+    .trim();
+    check_errors_match(code, NO_ERRORS);
+}
@@ -0,0 +1,56 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+#[test]
+fn parallel_matches_serial() {
+    let code = r##"
+/proc/sleeper()
+    sleep(1)
+
+/proc/first()
+    set SpacemanDMM_should_not_sleep = TRUE
+    sleeper()
+
+/proc/second(a, b)
+    var/list/L = list()
+    L[1].name
+    return a
+
+/proc/third()
+    second(c = 1)
+    second(a = 1)
+
+/datum/proc/fourth()
+    set SpacemanDMM_should_call_parent = TRUE
+
+/datum/foo/fourth()
+    var/unused
+    return
+
+/datum/foo/proc/fifth()
+    src.name.foo
+"##
+    .trim();
+
+    let describe = |context: &dreammaker::Context| {
+        context
+            .errors()
+            .iter()
+            .map(|error| {
+                format!(
+                    "{}:{}: {}",
+                    error.location().line,
+                    error.location().column,
+                    error.description()
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let serial = describe(&parse_a_file_for_test(code));
+    assert!(!serial.is_empty());
+    for _ in 0..4 {
+        assert_eq!(describe(&parse_a_file_for_test_parallel(code)), serial);
+    }
+}
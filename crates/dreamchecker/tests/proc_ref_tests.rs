@@ -0,0 +1,49 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const PROC_REF_ERRORS: &[(u32, u16, &str)] = &[
+    (12, 5, "undefined proc: \"fo\" on /datum/thing"),
+    (13, 15, "undefined proc: \"bar\" on /datum/thing"),
+    (14, 5, "undefined proc: \"baz\" on /datum/thing"),
+    (
+        15,
+        5,
+        "too many arguments to /datum/thing/proc/foo: expected at most 1, got 2",
+    ),
+    (16, 5, "bad keyword argument \"b\" to /datum/thing/proc/foo"),
+    (17, 13, "undefined proc: \"nope\" on /datum/thing"),
+    (
+        19,
+        5,
+        "too many arguments to /datum/thing/proc/foo: expected at most 1, got 3",
+    ),
+];
+
+#[test]
+fn proc_references() {
+    let code = r##"
+#define PROC_REF(X) (nameof(.proc/##X))
+/datum/callback/New(thing, proc)
+
+/datum/thing/proc/foo(a)
+
+/datum/thing/proc/varargs(...)
+
+/datum/thing/proc/test()
+    call(src, "foo")(1)
+    call(src, .proc/foo)()
+    call(src, PROC_REF(varargs))(1, 2, 3)
+    call(src, "fo")()
+    call(src, /datum/thing/proc/bar)()
+    call(src, "baz")()
+    call(src, "foo")(1, 2)
+    call(src, "foo")(b = 2)
+    var/x = /datum/thing/proc/nope
+    new /datum/callback(src, PROC_REF(foo), 1)
+    new /datum/callback(src, PROC_REF(foo), 1, 2, 3)
+    return x
+"##
+    .trim();
+    check_errors_match(code, PROC_REF_ERRORS);
+}
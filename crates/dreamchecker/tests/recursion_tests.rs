@@ -0,0 +1,56 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+const RECURSION_CONFIG: &str = r#"
+[diagnostics]
+recursion = "warning"
+"#;
+
+pub const RECURSION_ERRORS: &[(u32, u16, &str)] = &[
+    (1, 16, "/proc/countdown calls itself recursively"),
+    (5, 11, "/proc/ping is mutually recursive with /proc/pong"),
+];
+
+#[test]
+fn recursion() {
+    let code = r##"
+/proc/countdown(n)
+    if(n > 0)
+        countdown(n - 1)
+
+/proc/ping()
+    pong()
+
+/proc/pong()
+    ping()
+
+/proc/respawn()
+    spawn(10)
+        respawn()
+
+/proc/walk_tree(list/L)
+    set SpacemanDMM_allow_recursion = TRUE
+    for(var/child in L)
+        walk_tree(child)
+
+/datum/proc/visit()
+    set SpacemanDMM_allow_recursion = TRUE
+
+/datum/thing/visit(datum/other)
+    other.visit()
+"##
+    .trim();
+    check_errors_match_with_config(code, RECURSION_CONFIG, RECURSION_ERRORS);
+}
+
+#[test]
+fn recursion_opt_in() {
+    let code = r##"
+/proc/countdown(n)
+    if(n > 0)
+        countdown(n - 1)
+"##
+    .trim();
+    check_errors_match(code, NO_ERRORS);
+}
@@ -0,0 +1,74 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+use std::path::PathBuf;
+
+/// Create a fresh directory named after the test containing `files`.
+fn resource_root(test: &str, files: &[&str]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("dreamchecker-resource-tests-{}", test));
+    let _ = std::fs::remove_dir_all(&root);
+    for file in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+    }
+    root
+}
+
+pub const RESOURCE_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        5,
+        17,
+        "resource 'sound/Alarm.ogg' does not match the case of the file on disk: 'sound/alarm.ogg'",
+    ),
+    (6, 17, "resource not found: 'sound/missing.ogg'"),
+    (9, 14, "resource not found: 'sound/gone.ogg'"),
+];
+
+#[test]
+fn missing_and_miscased() {
+    let code = r##"
+#define FILE_DIR "sounds"
+/obj/alarm
+    var/sound_a = 'sound/alarm.ogg'
+    var/sound_b = 'beep.ogg'
+    var/sound_c = 'sound/Alarm.ogg'
+    var/sound_d = 'sound/missing.ogg'
+
+/obj/alarm/proc/ring()
+    world << 'sound/gone.ogg'
+    world << 'sound/alarm.ogg'
+"##
+    .trim();
+    let root = resource_root("missing", &["sound/alarm.ogg", "sounds/beep.ogg"]);
+    check_resource_errors_match(code, "", &root, RESOURCE_ERRORS);
+}
+
+pub const UNUSED_ERRORS: &[(u32, u16, &str)] =
+    &[(1, 1, "resource is never referenced: 'icons/old.dmi'")];
+
+#[test]
+fn unused_resources() {
+    let code = r##"
+/obj/thing
+    icon = 'icons/thing.dmi'
+
+/obj/thing/proc/show()
+    usr << browse_rsc(file("html/logo.png"))
+"##
+    .trim();
+    let root = resource_root(
+        "unused",
+        &[
+            "icons/thing.dmi",
+            "icons/old.dmi",
+            "html/logo.png",
+            "notes.txt",
+        ],
+    );
+    let config = r#"
+[diagnostics]
+unused_resource = "hint"
+"#;
+    check_resource_errors_match(code, config, &root, UNUSED_ERRORS);
+}
@@ -0,0 +1,70 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const IGNORE_ERRORS: &[(u32, u16, &str)] = &[
+    (12, 5, "proc has no parent: /proc/c"),
+    (
+        15,
+        5,
+        "unused suppression: no proc_has_no_parent diagnostic here",
+    ),
+];
+
+#[test]
+fn ignore() {
+    let code = r##"
+#define FOO 1
+#define FOO 2 // dreamchecker:ignore macro_redefined
+
+/proc/a()
+    // dreamchecker:ignore proc_has_no_parent
+    ..()
+
+/proc/b()
+    ..() // dreamchecker:ignore proc_has_no_parent - global procs have no parent
+
+/proc/c()
+    ..()
+
+/proc/d()
+    // dreamchecker:ignore proc_has_no_parent
+    return
+"##
+    .trim();
+    check_errors_match(code, IGNORE_ERRORS);
+}
+
+pub const IGNORE_FILE_ERRORS: &[(u32, u16, &str)] = &[(
+    1,
+    1,
+    "unused suppression: no macro_redefined diagnostic here",
+)];
+
+#[test]
+fn ignore_file() {
+    let code = r##"
+// dreamchecker:ignore-file proc_has_no_parent, macro_redefined
+/proc/a()
+    ..()
+
+/proc/b()
+    ..()
+"##
+    .trim();
+    check_errors_match(code, IGNORE_FILE_ERRORS);
+}
+
+#[test]
+fn unused_suppression_disabled() {
+    let code = r##"
+/proc/a()
+    return // dreamchecker:ignore proc_has_no_parent
+"##
+    .trim();
+    let config = r#"
+[diagnostics]
+unused_suppression = "off"
+"#;
+    check_errors_match_with_config(code, config, NO_ERRORS);
+}
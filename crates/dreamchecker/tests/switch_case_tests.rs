@@ -0,0 +1,167 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const SWITCH_DUPLICATE_ERRORS: &[(u32, u16, &str)] = &[
+    (11, 12, "duplicate case 2"),
+    (13, 12, "case 4 overlaps earlier case 3 to 5"),
+    (15, 12, "case 5 to 7 overlaps earlier case 3 to 5"),
+    (17, 17, "duplicate case \"a\""),
+    (24, 12, "duplicate case MODE_IDLE (1)"),
+];
+
+#[test]
+fn switch_duplicate_cases() {
+    let code = r##"
+#define MODE_OFF 0
+#define MODE_ON 1
+#define MODE_IDLE 1
+
+/proc/test(x)
+    switch(x)
+        if(1, 2)
+            x++
+        if(3 to 5)
+            x++
+        if(2)
+            x++
+        if(4)
+            x++
+        if(5 to 7, 9)
+            x++
+        if("a", "a")
+            x++
+    switch(x)
+        if(MODE_OFF)
+            x++
+        if(MODE_ON)
+            x++
+        if(MODE_IDLE)
+            x++
+"##
+    .trim();
+    check_errors_match(code, SWITCH_DUPLICATE_ERRORS);
+}
+
+pub const SWITCH_IMPOSSIBLE_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        5,
+        12,
+        "case \"2\" can never match: the switch is on a number",
+    ),
+    (
+        10,
+        12,
+        "case \"a\" can never match: the switch is on a number",
+    ),
+    (15, 12, "case 1 to 2 can never match: the switch is on text"),
+];
+
+#[test]
+fn switch_impossible_cases() {
+    let code = r##"
+/proc/test(x)
+    switch(rand(1, 2))
+        if(1)
+            x++
+        if("2")
+            x++
+        else
+            x++
+    switch(x % 3)
+        if("a")
+            x++
+    switch(lowertext(x))
+        if("a")
+            x++
+        if(1 to 2)
+            x++
+        if(null)
+            x++
+    switch(x)
+        if("a", 1, /obj)
+            x++
+"##
+    .trim();
+    check_errors_match(code, SWITCH_IMPOSSIBLE_ERRORS);
+}
+
+#[test]
+fn switch_reassigned_local() {
+    let code = r##"
+/proc/get_mode()
+    return "fast"
+
+/proc/test(x)
+    var/mode = 0
+    mode = get_mode()
+    switch(mode)
+        if("fast")
+            x++
+        if("slow")
+            x++
+    var/speed = 1
+    for(var/i in 1 to 3)
+        speed = "[i]"
+    switch(speed)
+        if("1")
+            x++
+"##
+    .trim();
+    check_errors_match(code, &[]);
+}
+
+const ENABLED: &str = r#"
+[diagnostics]
+missing_switch_else = "warning"
+"#;
+
+pub const SWITCH_MISSING_ELSE_ERRORS: &[(u32, u16, &str)] = &[
+    (
+        9,
+        5,
+        "switch over DIR_* has no else, and doesn't handle DIR_LEFT",
+    ),
+    (
+        27,
+        5,
+        "switch over STATE_* has no else, and doesn't handle STATE_A",
+    ),
+];
+
+#[test]
+fn switch_missing_else() {
+    let code = r##"
+#define DIR_UP "up"
+#define DIR_DOWN "down"
+#define DIR_LEFT "left"
+
+#define STATE_A 1
+#define STATE_B 2
+
+/proc/test(x)
+    switch(x)
+        if(DIR_UP)
+            x++
+        if(DIR_DOWN)
+            x++
+    switch(x)
+        if(DIR_UP, DIR_DOWN, DIR_LEFT)
+            x++
+    switch(x)
+        if(DIR_UP)
+            x++
+        else
+            x++
+    switch(x)
+        if(STATE_A)
+            x++
+        if(3)
+            x++
+    switch(x)
+        if(STATE_B)
+            x++
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, SWITCH_MISSING_ELSE_ERRORS);
+}
@@ -0,0 +1,90 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+const ENABLED: &str = r#"
+[diagnostics]
+type_mismatch_assignment = "warning"
+type_mismatch_argument = "warning"
+type_mismatch_return = "warning"
+"#;
+
+pub const TYPE_MISMATCH_ERRORS: &[(u32, u16, &str)] = &[
+    (7, 5, "type mismatch: assigning /obj to A, which is a /mob"),
+    (8, 5, "type mismatch: assigning /mob to B, which is a /obj"),
+    (9, 5, "type mismatch: assigning /obj to A, which is a /mob"),
+    (
+        10,
+        5,
+        "type mismatch: assigning /mob to held, which is a /obj",
+    ),
+    (
+        12,
+        6,
+        "type mismatch: passing /obj as target to /mob/proc/target, which expects a /mob",
+    ),
+    (
+        13,
+        6,
+        "type mismatch: passing /obj as target to /mob/proc/target, which expects a /mob",
+    ),
+    (
+        5,
+        5,
+        "type mismatch: /mob/proc/get_mob returns /obj, but its return type is /mob",
+    ),
+];
+
+#[test]
+fn type_mismatch() {
+    let code = r##"
+/mob/var/obj/held
+/mob/proc/target(mob/target)
+/mob/proc/get_mob()
+    set SpacemanDMM_return_type = /mob
+    return new /obj
+/proc/test_assign(mob/M)
+    var/mob/A = new /obj
+    var/obj/B = M
+    A = B
+    M.held = M
+/proc/test_args(mob/M, obj/O)
+    M.target(O)
+    M.target(target = O)
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, TYPE_MISMATCH_ERRORS);
+}
+
+#[test]
+fn type_mismatch_compatible() {
+    let code = r##"
+/mob/living
+/mob/var/atom/movable/held
+/mob/proc/target(mob/target)
+/mob/proc/get_mob()
+    set SpacemanDMM_return_type = /mob
+    return new /mob/living
+/proc/test(mob/M, datum/D, x)
+    var/mob/A = D
+    var/mob/B = x
+    var/atom/C = M
+    var/mob/E = null
+    M.held = new /obj
+    M.target(M)
+    M.target(D)
+    M.target(x)
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, NO_ERRORS);
+}
+
+#[test]
+fn type_mismatch_opt_in() {
+    let code = r##"
+/proc/test()
+    var/mob/A = new /obj
+"##
+    .trim();
+    check_errors_match(code, NO_ERRORS);
+}
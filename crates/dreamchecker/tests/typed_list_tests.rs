@@ -0,0 +1,63 @@
+use dreamchecker as dc;
+
+use dc::test_helpers::*;
+
+pub const TYPED_LIST_FLOW_ERRORS: &[(u32, u16, &str)] = &[
+    (6, 12, "undefined proc: \"fly\" on /mob"),
+    (8, 12, "undefined proc: \"fly\" on /obj"),
+    (9, 16, "undefined proc: \"fly\" on /obj"),
+    (10, 12, "undefined proc: \"fly\" on /mob"),
+    (12, 12, "undefined proc: \"fly\" on /mob"),
+    (13, 14, "undefined proc: \"fly\" on /obj"),
+];
+
+#[test]
+fn typed_list_flow() {
+    let code = r##"
+/mob/proc/walk()
+/obj/proc/walk()
+
+/proc/test(list/mob/SpacemanDMM_list_of/obj/held, mob/M)
+    for(var/mob in held)
+        mob.fly()
+    held[1].walk()
+    held[M].fly()
+    held["key"].fly()
+    held[1].fly()
+    for(var/key, value in held)
+        key.fly()
+        value.fly()
+"##
+    .trim();
+    check_errors_match(code, TYPED_LIST_FLOW_ERRORS);
+}
+
+const ENABLED: &str = r#"
+[diagnostics]
+type_mismatch_list = "warning"
+"#;
+
+pub const TYPED_LIST_ADD_ERRORS: &[(u32, u16, &str)] = &[
+    (7, 5, "type mismatch: adding /obj to a list of /mob"),
+    (8, 9, "type mismatch: adding /obj to a list of /mob"),
+    (9, 5, "type mismatch: adding /obj to a list of /mob"),
+];
+
+#[test]
+fn typed_list_add() {
+    let code = r##"
+/mob/living
+
+/proc/test(obj/O, mob/living/M, list/obj/objs)
+    var/list/mob/mobs = list()
+    mobs += M
+    mobs.Add(M, list())
+    mobs += O
+    mobs.Add(M, O)
+    mobs |= objs
+    mobs += list(O)
+    mobs.Add(mobs)
+"##
+    .trim();
+    check_errors_match_with_config(code, ENABLED, TYPED_LIST_ADD_ERRORS);
+}
@@ -567,6 +567,7 @@ bitflags! {
         const FINAL = 1 << 4;
         const PRIVATE = 1 << 5;
         const PROTECTED = 1 << 6;
+        const DEPRECATED = 1 << 7;
     }
 }
 
@@ -584,6 +585,7 @@ impl VarTypeFlags {
             "SpacemanDMM_final" => Some(VarTypeFlags::FINAL),
             "SpacemanDMM_private" => Some(VarTypeFlags::PRIVATE),
             "SpacemanDMM_protected" => Some(VarTypeFlags::PROTECTED),
+            "SpacemanDMM_deprecated" => Some(VarTypeFlags::DEPRECATED),
             // Fallback
             _ => None,
         }
@@ -619,6 +621,11 @@ impl VarTypeFlags {
         self.contains(VarTypeFlags::PROTECTED)
     }
 
+    #[inline]
+    pub fn is_deprecated(&self) -> bool {
+        self.contains(VarTypeFlags::DEPRECATED)
+    }
+
     #[inline]
     pub fn is_const_evaluable(&self) -> bool {
         self.contains(VarTypeFlags::CONST)
@@ -650,6 +657,9 @@ impl VarTypeFlags {
         if self.is_protected() {
             v.push("SpacemanDMM_protected");
         }
+        if self.is_deprecated() {
+            v.push("SpacemanDMM_deprecated");
+        }
         v
     }
 }
@@ -674,6 +684,9 @@ impl fmt::Display for VarTypeFlags {
         if self.is_protected() {
             fmt.write_str("SpacemanDMM_protected/")?;
         }
+        if self.is_deprecated() {
+            fmt.write_str("SpacemanDMM_deprecated/")?;
+        }
         Ok(())
     }
 }
@@ -1,16 +1,34 @@
 //! Configuration file for diagnostics.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use ahash::RandomState;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 
 use crate::DMError;
 use crate::error::Severity;
 
+/// Diagnostics which are disabled unless given a level in `[diagnostics]`.
+const OPT_IN_DIAGNOSTICS: &[&str] = &[
+    "possible_null_deref",
+    "type_mismatch_assignment",
+    "type_mismatch_argument",
+    "type_mismatch_return",
+    "type_mismatch_list",
+    "unused_local_var",
+    "unused_parameter",
+    "use_before_assign",
+    "shadowed_var",
+    "recursion",
+    "missing_switch_else",
+    "icon_state_unused",
+    "unused_resource",
+];
+
 /// Struct for deserializing from a config TOML
 #[derive(Deserialize, Default, Debug, Clone)]
 #[serde(default)]
@@ -21,6 +39,7 @@ pub struct Config {
     display: WarningDisplay,
     diagnostics: HashMap<String, WarningLevel, RandomState>,
     pub code_standards: CodeStandards,
+    pub lints: Vec<LintRule>,
 
     // tool-specific configuration
     pub langserver: Langserver,
@@ -59,11 +78,52 @@ pub struct DMDoc {
     pub module_directories: Vec<String>,
 }
 
+/// A project-specific rule from a `[[lints]]` table.
+///
+/// A rule may set any of `forbid_call`, `forbid_read`, `forbid_write`, and
+/// `require_parent_call`. Targets are either a bare name or qualified by a
+/// typepath, as in `"/mob/proc/gib"` or `"/atom/var/density"`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LintRule {
+    pub errortype: Errortype,
+    #[serde(default)]
+    pub severity: WarningLevel,
+    /// Explanation appended to each report.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Typepaths whose procs the rule applies to, with their subtypes. All
+    /// procs if empty.
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// Typepaths which are exempt, with their subtypes.
+    #[serde(default)]
+    pub exclude_types: Vec<String>,
+    /// Whether the rule applies only inside verbs.
+    #[serde(default)]
+    pub verbs_only: bool,
+
+    /// Builtin or user proc which may not be called.
+    #[serde(default)]
+    pub forbid_call: Option<String>,
+    /// Var which may not be read.
+    #[serde(default)]
+    pub forbid_read: Option<String>,
+    /// Var which may not be assigned to.
+    #[serde(default)]
+    pub forbid_write: Option<String>,
+    /// Proc whose overrides must call `..()`.
+    #[serde(default)]
+    pub require_parent_call: Option<String>,
+}
+
 // Debugger config options
 #[derive(Deserialize, Default, Debug, Clone)]
 pub struct Debugger {
     #[serde(default)]
     pub engine: DebugEngine,
+    /// Path to the DreamMaker compiler used when restarting a launched session.
+    #[serde(default)]
+    pub dreammaker_exe: Option<String>,
 }
 
 /// Severity overrides from configuration
@@ -117,7 +177,12 @@ impl Config {
         let mut file = File::open(path)?;
         let mut config_toml = String::new();
         file.read_to_string(&mut config_toml)?;
-        Ok(toml::from_str(&config_toml)?)
+        Config::from_toml(&config_toml)
+    }
+
+    /// Parse a config from TOML text.
+    pub fn from_toml(config_toml: &str) -> Result<Config, Error> {
+        Ok(toml::from_str(config_toml)?)
     }
 
     fn config_warninglevel(&self, error: &DMError) -> Option<&WarningLevel> {
@@ -139,7 +204,15 @@ impl Config {
             Some(WarningLevel::Info) => error.set_severity(Severity::Info),
             Some(WarningLevel::Hint) => error.set_severity(Severity::Hint),
             Some(WarningLevel::Disabled) => return None,
-            Some(WarningLevel::Unset) | None => error,
+            Some(WarningLevel::Unset) | None => {
+                if error
+                    .errortype()
+                    .is_some_and(|errortype| OPT_IN_DIAGNOSTICS.contains(&errortype))
+                {
+                    return None;
+                }
+                error
+            }
         })
     }
 
@@ -149,6 +222,57 @@ impl Config {
     }
 }
 
+impl LintRule {
+    /// The severity to report at, or `None` if the rule is disabled.
+    pub fn severity(&self) -> Option<Severity> {
+        match self.severity {
+            WarningLevel::Error => Some(Severity::Error),
+            WarningLevel::Warning | WarningLevel::Unset => Some(Severity::Warning),
+            WarningLevel::Info => Some(Severity::Info),
+            WarningLevel::Hint => Some(Severity::Hint),
+            WarningLevel::Disabled => None,
+        }
+    }
+
+    /// Split a target into its typepath, if qualified, and its name.
+    ///
+    /// `"/mob/proc/gib"` and `"/mob/gib"` give `(Some("/mob"), "gib")`, and
+    /// `"/proc/sleep"` gives `(Some(""), "sleep")` for the root type.
+    pub fn split_target(target: &str) -> (Option<&str>, &str) {
+        match target.rsplit_once('/') {
+            Some((path, name)) => {
+                let path = ["/proc", "/verb", "/var"]
+                    .iter()
+                    .find_map(|suffix| path.strip_suffix(suffix))
+                    .unwrap_or(path);
+                (Some(path), name)
+            }
+            None => (None, target),
+        }
+    }
+}
+
+/// An errortype named by configuration, interned so it can be attached to
+/// errors like the built-in ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Errortype(pub &'static str);
+
+impl<'de> Deserialize<'de> for Errortype {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Each name is leaked only once, however often the config is reloaded.
+        static INTERNED: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+        let errortype = String::deserialize(deserializer)?;
+        let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(&existing) = interned.get(errortype.as_str()) {
+            return Ok(Errortype(existing));
+        }
+        let leaked: &'static str = Box::leak(errortype.into_boxed_str());
+        interned.insert(leaked);
+        Ok(Errortype(leaked))
+    }
+}
+
 impl WarningLevel {
     fn applies_to(self, severity: Severity) -> bool {
         match self {
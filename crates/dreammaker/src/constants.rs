@@ -462,6 +462,7 @@ impl Expression {
             location,
             ty: NodeIndex::new(0),
             defines: None,
+            context: None,
         }
         .expr(self, None)
     }
@@ -478,6 +479,7 @@ pub fn preprocessor_evaluate(
         location,
         ty: NodeIndex::new(0),
         defines: Some(defines),
+        context: None,
     }
     .expr(expr, None)
 }
@@ -494,7 +496,7 @@ pub(crate) fn evaluate_all(context: &Context, tree: &mut ObjectTree) {
             }) {
                 continue; // skip non-constant-evaluable vars
             }
-            match constant_ident_lookup(tree, ty, &key, false) {
+            match constant_ident_lookup(Some(context), tree, ty, &key, false) {
                 Err(err) => context.register_error(err),
                 Ok(ConstLookup::Found(_, _)) => {}
                 Ok(ConstLookup::Continue(_)) => {
@@ -508,6 +510,106 @@ pub(crate) fn evaluate_all(context: &Context, tree: &mut ObjectTree) {
     }
 }
 
+/// Check a unary operation on a constant for a result which silently differs
+/// from what was written, returning a description and errortype.
+pub fn check_unary_op(op: UnaryOp, term: &Constant) -> Option<(String, &'static str)> {
+    match (op, term) {
+        (UnaryOp::BitNot, &Constant::Float(f)) => bitwise_operand_overflow(f),
+        _ => None,
+    }
+}
+
+/// Check a binary operation, either side of which may be a known constant,
+/// for a result which silently differs from what was written, returning a
+/// description and errortype.
+pub fn check_binary_op(
+    op: BinaryOp,
+    lhs: Option<&Constant>,
+    rhs: Option<&Constant>,
+) -> Option<(String, &'static str)> {
+    let lhs = lhs.and_then(Constant::to_float);
+    let rhs = rhs.and_then(Constant::to_float);
+    match op {
+        BinaryOp::Div | BinaryOp::Mod | BinaryOp::FloatMod if rhs == Some(0.0) => Some((
+            format!(
+                "{} by constant zero",
+                if op == BinaryOp::Div {
+                    "division"
+                } else {
+                    "modulo"
+                }
+            ),
+            "division_by_zero",
+        )),
+        BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => lhs
+            .and_then(bitwise_operand_overflow)
+            .or_else(|| rhs.and_then(bitwise_operand_overflow)),
+        BinaryOp::LShift | BinaryOp::RShift => {
+            if let Some(count) = rhs.filter(|&count| count < 0.0) {
+                return Some((
+                    format!("shift by negative count {}", count),
+                    "negative_shift",
+                ));
+            }
+            if let Some(overflow) = lhs.and_then(bitwise_operand_overflow) {
+                return Some(overflow);
+            }
+            match (op, lhs, rhs) {
+                (BinaryOp::LShift, Some(lhs), Some(rhs))
+                    if (lhs as u64).checked_shl(rhs as u32).unwrap_or(u64::MAX)
+                        > Constant::BIT_MASK as u64 =>
+                {
+                    Some((
+                        format!("{} << {} overflows 24 bits and is truncated", lhs, rhs),
+                        "bitwise_overflow",
+                    ))
+                }
+                _ => None,
+            }
+        }
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+            let (lhs, rhs) = (lhs?, rhs?);
+            if lhs.fract() != 0.0 || rhs.fract() != 0.0 {
+                return None;
+            }
+            let exact = match op {
+                BinaryOp::Add => lhs as f64 + rhs as f64,
+                BinaryOp::Sub => lhs as f64 - rhs as f64,
+                _ => lhs as f64 * rhs as f64,
+            };
+            if exact.abs() > MAX_EXACT_INTEGER as f64 && exact as f32 as f64 != exact {
+                Some((
+                    format!(
+                        "precision loss of integer arithmetic: {} {} {} is {}, not {}",
+                        lhs, op, rhs, exact as f32, exact
+                    ),
+                    "integer_precision_loss",
+                ))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The largest integer below which every integer is exactly representable.
+const MAX_EXACT_INTEGER: f32 = 16777216.0;
+
+fn bitwise_operand_overflow(operand: f32) -> Option<(String, &'static str)> {
+    if operand > Constant::BIT_MASK as f32 {
+        Some((
+            format!(
+                "bitwise operand {} exceeds 24 bits and is truncated",
+                operand
+            ),
+            "bitwise_overflow",
+        ))
+    } else {
+        None
+    }
+}
+
 #[allow(dead_code)]
 enum ConstLookup {
     Found(TreePath, Constant),
@@ -515,6 +617,7 @@ enum ConstLookup {
 }
 
 fn constant_ident_lookup(
+    context: Option<&Context>,
     tree: &mut ObjectTree,
     ty: NodeIndex,
     ident: &str,
@@ -568,6 +671,7 @@ fn constant_ident_lookup(
     let value = ConstantFolder {
         tree: Some(tree),
         defines: None,
+        context,
         location,
         ty,
     }
@@ -589,6 +693,8 @@ fn constant_ident_lookup(
 struct ConstantFolder<'a> {
     tree: Option<&'a mut ObjectTree>,
     defines: Option<&'a DefineMap>,
+    /// Where to report warnings, if anywhere.
+    context: Option<&'a Context>,
     location: Location,
     ty: NodeIndex,
 }
@@ -732,9 +838,20 @@ impl ConstantFolder<'_> {
         }
     }
 
+    fn warn(&self, warning: Option<(String, &'static str)>) {
+        if let (Some(context), Some((desc, errortype))) = (self.context, warning) {
+            self.error(desc)
+                .set_severity(Severity::Warning)
+                .with_errortype(errortype)
+                .register(context);
+        }
+    }
+
     fn unary(&mut self, term: Constant, op: UnaryOp) -> Result<Constant, DMError> {
         use self::Constant::*;
 
+        self.warn(check_unary_op(op, &term));
+
         Ok(match (op, term) {
             // int ops
             (UnaryOp::Neg, Float(i)) => Float(-i),
@@ -759,6 +876,8 @@ impl ConstantFolder<'_> {
     ) -> Result<Constant, DMError> {
         use self::Constant::*;
 
+        self.warn(check_binary_op(op, Some(&lhs), Some(&rhs)));
+
         macro_rules! numeric {
             ($name:ident $oper:tt) => {
                 match (op, lhs, rhs) {
@@ -805,8 +924,23 @@ impl ConstantFolder<'_> {
         integer!(BitOr |);
         integer!(BitAnd &);
         integer!(BitXor ^);
-        integer!(LShift <<);
-        integer!(RShift >>);
+
+        match (op, lhs, rhs) {
+            (BinaryOp::LShift, Float(lhs), Float(rhs)) => {
+                return Ok(Constant::from_bit_op(
+                    (lhs as u32).checked_shl(rhs as u32).unwrap_or(0),
+                ));
+            }
+            (BinaryOp::RShift, Float(lhs), Float(rhs)) => {
+                return Ok(Constant::from_bit_op(
+                    (lhs as u32).checked_shr(rhs as u32).unwrap_or(0),
+                ));
+            }
+            (_, lhs_, rhs_) => {
+                lhs = lhs_;
+                rhs = rhs_;
+            }
+        }
 
         match (op, lhs, rhs) {
             (BinaryOp::Add, String(lhs), String(rhs)) => {
@@ -1057,7 +1191,7 @@ impl ConstantFolder<'_> {
                 )));
             }
             let tree = self.tree.as_mut().unwrap();
-            match constant_ident_lookup(tree, ty, ident, must_be_const)
+            match constant_ident_lookup(self.context, tree, ty, ident, must_be_const)
                 .map_err(|e| e.with_location(location))?
             {
                 ConstLookup::Found(_, v) => return Ok(v),
@@ -11,6 +11,8 @@ use get_size::GetSize;
 use termcolor::{Color, ColorSpec};
 
 use crate::config::Config;
+use crate::constants::Constant;
+use crate::preprocessor::LiteralMacros;
 
 /// An identifier referring to a loaded file.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -47,10 +49,61 @@ pub struct Context {
     /// Warning config
     config: RefCell<Config>,
     print_severity: Option<Severity>,
+    /// `// dreamchecker:ignore` comments seen by the lexer.
+    suppressions: RefCell<HashMap<FileId, FileSuppressions, RandomState>>,
+    /// Macros defined as a single literal, seen by the preprocessor.
+    literal_macros: RefCell<LiteralMacros>,
 
     io_time: std::cell::Cell<std::time::Duration>,
 }
 
+/// A `// dreamchecker:ignore` comment for one errortype.
+#[derive(Debug, Clone)]
+struct Suppression {
+    errortype: String,
+    location: Location,
+    used: bool,
+}
+
+impl Suppression {
+    /// Whether this suppression hides the given error, given the lines it
+    /// covers. Marks it used if so.
+    fn take(&mut self, error: &DMError, lines: std::ops::RangeInclusive<u32>) -> bool {
+        if error.errortype() == Some(self.errortype.as_str())
+            && lines.contains(&error.location.line)
+        {
+            self.used = true;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The suppressions in one file.
+#[derive(Debug, Default, Clone)]
+struct FileSuppressions {
+    /// From `// dreamchecker:ignore-file`.
+    whole_file: Vec<Suppression>,
+    /// From `// dreamchecker:ignore`, which covers its own line and the next.
+    lines: Vec<Suppression>,
+}
+
+impl FileSuppressions {
+    fn take(&mut self, error: &DMError) -> bool {
+        // Check everything so that overlapping suppressions are all used.
+        let mut suppressed = false;
+        for each in self.whole_file.iter_mut() {
+            suppressed |= each.take(error, 0..=u32::MAX);
+        }
+        for each in self.lines.iter_mut() {
+            let line = each.location.line;
+            suppressed |= each.take(error, line..=line + 1);
+        }
+        suppressed
+    }
+}
+
 impl FileList {
     /// Add a new file to the context and return its index.
     pub fn register(&self, path: &Path) -> FileId {
@@ -155,6 +208,14 @@ impl Context {
         self.config.borrow()
     }
 
+    /// Create an empty context which uses the given configuration.
+    pub fn with_config(config: Config) -> Context {
+        Context {
+            config: RefCell::new(config),
+            ..Default::default()
+        }
+    }
+
     /// Set a severity at and above which errors will be printed immediately.
     pub fn set_print_severity(&mut self, print_severity: Option<Severity>) {
         self.print_severity = print_severity;
@@ -175,6 +236,92 @@ impl Context {
         self.io_time.get()
     }
 
+    // ------------------------------------------------------------------------
+    // Literal macros
+
+    /// Register a macro whose definition is a single literal.
+    pub fn register_literal_macro(&self, name: String, location: Location, value: Constant) {
+        self.literal_macros
+            .borrow_mut()
+            .define(name, location, value);
+    }
+
+    /// Register a use of the macro defined at `definition`, if it is a
+    /// literal macro.
+    pub fn register_literal_macro_use(&self, location: Location, definition: Location) {
+        self.literal_macros
+            .borrow_mut()
+            .use_at(location, definition);
+    }
+
+    /// Access the macros defined as a single literal, and where they were
+    /// used.
+    pub fn literal_macros(&self) -> Ref<'_, LiteralMacros> {
+        self.literal_macros.borrow()
+    }
+
+    // ------------------------------------------------------------------------
+    // Suppressions
+
+    /// Register a `// dreamchecker:ignore` comment at the given location,
+    /// covering its own line and the next, or the whole file if `whole_file`.
+    ///
+    /// Errors already registered which it covers are removed, since the
+    /// comment may come after them on the same line.
+    pub fn add_suppression(&self, location: Location, errortype: &str, whole_file: bool) {
+        let mut suppression = Suppression {
+            errortype: errortype.to_owned(),
+            location,
+            used: false,
+        };
+        let lines = if whole_file {
+            0..=u32::MAX
+        } else {
+            location.line..=location.line + 1
+        };
+        self.errors.borrow_mut().retain(|error| {
+            error.location.file != location.file || !suppression.take(error, lines.clone())
+        });
+
+        let mut suppressions = self.suppressions.borrow_mut();
+        let file = suppressions.entry(location.file).or_default();
+        if whole_file {
+            file.whole_file.push(suppression);
+        } else {
+            file.lines.push(suppression);
+        }
+    }
+
+    /// Forget the suppressions in a file which is about to be lexed again.
+    pub fn clear_suppressions(&self, file: FileId) {
+        self.suppressions.borrow_mut().remove(&file);
+    }
+
+    /// Report suppressions which did not hide any error. Call this after all
+    /// checks have run. Each suppression is reported at most once.
+    pub fn check_unused_suppressions(&self) {
+        let mut unused: Vec<(Location, String)> = self
+            .suppressions
+            .borrow_mut()
+            .values_mut()
+            .flat_map(|file| file.whole_file.iter_mut().chain(file.lines.iter_mut()))
+            .filter_map(|each| {
+                (!std::mem::replace(&mut each.used, true))
+                    .then(|| (each.location, each.errortype.clone()))
+            })
+            .collect();
+        unused.sort();
+        for (location, errortype) in unused {
+            DMError::new(
+                location,
+                format!("unused suppression: no {} diagnostic here", errortype),
+            )
+            .set_severity(Severity::Warning)
+            .with_errortype("unused_suppression")
+            .register(self);
+        }
+    }
+
     // ------------------------------------------------------------------------
     // Errors
 
@@ -183,6 +330,11 @@ impl Context {
         let Some(error) = self.config.borrow().set_configured_severity(error) else {
             return; // errortype is disabled
         };
+        if let Some(file) = self.suppressions.borrow_mut().get_mut(&error.location.file)
+            && file.take(&error)
+        {
+            return;
+        }
         // ignore errors with severity above configured level
         if !self.config.borrow().registerable_error(&error) {
             return;
@@ -289,7 +441,7 @@ impl Context {
 // Location handling
 
 /// File, line, and column information for an error.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Default, GetSize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Default, GetSize)]
 pub struct Location {
     /// The index into the file table.
     pub file: FileId,
@@ -905,8 +905,10 @@ impl<'ctx> Lexer<'ctx> {
             .map(Token::DocComment)
     }
 
-    fn skip_line_comment(&mut self) -> Option<Token> {
+    fn skip_line_comment(&mut self, start: Location) -> Option<Token> {
         let mut backslash = false;
+        // The text of ordinary comments, for `dreamchecker:ignore`.
+        let mut plain = String::new();
 
         // read the first character and check for being a comment
         let mut comment = None;
@@ -926,11 +928,11 @@ impl<'ctx> Lexer<'ctx> {
         }
 
         while let Some(ch) = self.next() {
-            if ch != b'\r'
-                && ch != b'\n'
-                && let Some(ref mut comment) = comment
-            {
-                comment.text.push(ch as char);
+            if ch != b'\r' && ch != b'\n' {
+                match comment {
+                    Some(ref mut comment) => comment.text.push(ch as char),
+                    None => plain.push(ch as char),
+                }
             }
 
             if ch == b'\r' {
@@ -952,9 +954,50 @@ impl<'ctx> Lexer<'ctx> {
             }
         }
 
+        if let Some(directive) = plain.trim_start().strip_prefix("dreamchecker:") {
+            self.read_suppression(start, directive);
+        }
         comment.map(Token::DocComment)
     }
 
+    /// Handle `// dreamchecker:ignore <errortype>...` and
+    /// `// dreamchecker:ignore-file <errortype>...`. Errortypes are separated
+    /// by commas, and anything after them is taken as an explanation.
+    fn read_suppression(&mut self, location: Location, directive: &str) {
+        let (name, rest) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        let whole_file = match name {
+            "ignore" => false,
+            "ignore-file" => true,
+            _ => return,
+        };
+        let mut errortypes = Vec::new();
+        let mut rest = rest.trim_start();
+        loop {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                break;
+            }
+            errortypes.push(&rest[..end]);
+            match rest[end..].trim_start().strip_prefix(',') {
+                Some(next) => rest = next.trim_start(),
+                None => break,
+            }
+        }
+        if errortypes.is_empty() {
+            DMError::new(location, "dreamchecker:ignore needs an errortype")
+                .set_severity(Severity::Warning)
+                .register(self.context);
+        }
+        for errortype in errortypes {
+            self.context
+                .add_suppression(location, errortype, whole_file);
+        }
+    }
+
     fn read_number_inner(&mut self, first: u8) -> (bool, u32, Cow<'static, str>) {
         let mut integer = true;
         let mut exponent = false;
@@ -1349,7 +1392,7 @@ impl Iterator for Lexer<'_> {
                     continue;
                 }
                 Some(LineComment) => {
-                    if let Some(t) = self.skip_line_comment() {
+                    if let Some(t) = self.skip_line_comment(loc) {
                         return Some(locate(t));
                     }
                     continue;
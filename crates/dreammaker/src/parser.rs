@@ -1851,6 +1851,15 @@ impl<'ctx, 'an, 'inp> Parser<'ctx, 'an, 'inp> {
                     .set_severity(Severity::Warning)
                     .register(self.context);
                 }
+                if var_type.flags.is_deprecated() {
+                    DMError::new(
+                        type_path_start,
+                        "var/SpacemanDMM_deprecated has no effect here",
+                    )
+                    .with_errortype("deprecated")
+                    .set_severity(Severity::Warning)
+                    .register(self.context);
+                }
                 let var_suffix = require!(self.var_suffix());
                 var_type.suffix(&var_suffix);
 
@@ -12,6 +12,7 @@ use interval_tree::{IntervalTree, range};
 
 use super::annotation::*;
 use super::ast::Ident;
+use super::constants::Constant;
 use super::docs::{DocCollection, DocComment, DocTarget};
 use super::lexer::*;
 use super::{Context, DMError, FileId, HasLocation, Location, Severity};
@@ -82,6 +83,85 @@ impl fmt::Display for NameAndDefine<'_> {
     }
 }
 
+/// A constant macro whose definition is a single literal, such as one member
+/// of an enumeration of `#define`s.
+#[derive(Debug, Clone)]
+pub struct LiteralMacro {
+    pub name: Ident,
+    pub location: Location,
+    pub value: Constant,
+}
+
+/// The literal macros seen by the preprocessor, and where they were used.
+#[derive(Debug, Clone, Default)]
+pub struct LiteralMacros {
+    defines: Vec<LiteralMacro>,
+    by_location: HashMap<Location, usize, RandomState>,
+    uses: HashMap<Location, usize, RandomState>,
+}
+
+impl LiteralMacros {
+    pub(crate) fn define(&mut self, name: Ident, location: Location, value: Constant) {
+        if self.by_location.contains_key(&location) {
+            return;
+        }
+        self.by_location.insert(location, self.defines.len());
+        self.defines.push(LiteralMacro {
+            name,
+            location,
+            value,
+        });
+    }
+
+    pub(crate) fn use_at(&mut self, location: Location, definition: Location) {
+        if let Some(&idx) = self.by_location.get(&definition) {
+            self.uses.insert(location, idx);
+        }
+    }
+
+    /// The literal macro whose expansion begins at the given location.
+    pub fn at(&self, location: Location) -> Option<&LiteralMacro> {
+        self.uses.get(&location).map(|&idx| &self.defines[idx])
+    }
+
+    /// The macros defined in the same file as `member` whose names share its
+    /// prefix up to the last `_`, such as `MODE_ON` and `MODE_OFF`.
+    pub fn family(&self, member: &LiteralMacro) -> Vec<&LiteralMacro> {
+        let Some(prefix) = family_prefix(&member.name) else {
+            return Vec::new();
+        };
+        let mut family: Vec<&LiteralMacro> = Vec::new();
+        for each in self.defines.iter() {
+            if each.location.file == member.location.file
+                && family_prefix(&each.name) == Some(prefix)
+                && !family.iter().any(|other| other.name == each.name)
+            {
+                family.push(each);
+            }
+        }
+        family
+    }
+}
+
+fn family_prefix(name: &str) -> Option<&str> {
+    match name.rfind('_') {
+        Some(idx) if idx > 0 => Some(&name[..=idx]),
+        _ => None,
+    }
+}
+
+/// The value of a macro whose definition is a single literal.
+fn literal_value(subst: &[Token]) -> Option<Constant> {
+    Some(match subst {
+        [Token::Int(i)] => Constant::Float(*i as f32),
+        [Token::Float(f)] => Constant::Float(*f),
+        [Token::Punct(Punctuation::Sub), Token::Int(i)] => Constant::Float(-(*i as f32)),
+        [Token::Punct(Punctuation::Sub), Token::Float(f)] => Constant::Float(-*f),
+        [Token::String(s)] => Constant::String(s.as_str().into()),
+        _ => return None,
+    })
+}
+
 type InnerDefineHistory = IntervalTree<Location, (String, Define)>;
 
 /// An interval tree representing historic macro definitions.
@@ -999,6 +1079,15 @@ impl<'ctx> Preprocessor<'ctx> {
                                 }
                             }
                         }
+                        if params.is_empty()
+                            && let Some(value) = literal_value(&subst)
+                        {
+                            self.context.register_literal_macro(
+                                define_name.clone(),
+                                define_name_loc,
+                                value,
+                            );
+                        }
                         let define = if params.is_empty() {
                             Define::Constant {
                                 subst,
@@ -1160,6 +1249,10 @@ impl<'ctx> Preprocessor<'ctx> {
                 match expansion {
                     Some((location, Define::Constant { subst, docs })) => {
                         self.annotate_macro(ident, location, Some(docs));
+                        if !self.include_stack.in_expansion() {
+                            self.context
+                                .register_literal_macro_use(self.last_input_loc, location);
+                        }
                         self.include_stack.stack.push(Include::Expansion {
                             //name: ident.to_owned(),
                             tokens: subst.into_iter().collect(),